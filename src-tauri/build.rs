@@ -1,3 +1,25 @@
+use std::process::Command;
+
+/// Run a command and return its trimmed stdout, or None if it fails
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
 fn main() {
+    // Embed build metadata for get_app_version; both are optional (e.g. building from a tarball)
+    if let Some(commit) = command_output("git", &["rev-parse", "--short", "HEAD"]) {
+        println!("cargo:rustc-env=WOODEYE_GIT_COMMIT={}", commit);
+    }
+    if let Some(date) = command_output("date", &["-u", "+%Y-%m-%dT%H:%M:%SZ"]) {
+        println!("cargo:rustc-env=WOODEYE_BUILD_DATE={}", date);
+    }
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+
     tauri_build::build()
 }
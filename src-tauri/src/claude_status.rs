@@ -1,10 +1,22 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Version of the status file format written by the hooks. Bump it when the
+/// format changes incompatibly so older readers skip files they can't parse.
+pub const STATUS_SCHEMA_VERSION: u32 = 1;
+
+fn default_schema() -> u32 {
+    // Files written before the schema field existed
+    1
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClaudeSession {
+    #[serde(default = "default_schema")]
+    pub schema: u32,
     pub project_path: String,
     pub session_id: String,
     pub state: String, // "working", "idle", "waiting_for_approval"
@@ -12,6 +24,79 @@ pub struct ClaudeSession {
     pub name: Option<String>, // Extracted from first prompt
     #[serde(skip_deserializing)]
     pub raw_json: String,
+    /// Whether the session has sat in its state longer than the state's stale threshold
+    #[serde(skip_deserializing)]
+    pub stale: bool,
+}
+
+impl ClaudeSession {
+    /// Whether the session is blocked waiting on the user
+    pub fn is_waiting(&self) -> bool {
+        self.state.starts_with("waiting")
+    }
+}
+
+/// States written by the Woodeye hooks
+pub const SESSION_STATES: [&str; 3] = ["working", "idle", "waiting_for_approval"];
+
+/// Default stale threshold for waiting and idle sessions
+pub const DEFAULT_STALE_THRESHOLD_SECS: u64 = 600;
+
+/// Get how long a session can stay in `state` before it is considered stale.
+/// Configured thresholds take precedence; otherwise waiting and idle states use
+/// the default and working sessions never go stale.
+pub fn get_stale_threshold_for_state(
+    state: &str,
+    configured: &HashMap<String, u64>,
+) -> Option<u64> {
+    if let Some(threshold) = configured.get(state) {
+        return Some(*threshold);
+    }
+    if state.starts_with("waiting") || state == "idle" {
+        Some(DEFAULT_STALE_THRESHOLD_SECS)
+    } else {
+        None
+    }
+}
+
+/// Claude activity attributed to a single worktree
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorktreeClaudeStatus {
+    pub sessions: Vec<ClaudeSession>,
+    /// Whether any of the sessions is waiting on the user
+    pub waiting: bool,
+}
+
+impl WorktreeClaudeStatus {
+    pub fn new(sessions: Vec<ClaudeSession>) -> Self {
+        let waiting = sessions.iter().any(|s| s.is_waiting());
+        Self { sessions, waiting }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApplyHooksResult {
+    /// Woodeye hook events left out because Claude doesn't support them
+    pub skipped_events: Vec<String>,
+}
+
+/// Whether the `claude` CLI can be found from Woodeye's environment
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaudeCliInfo {
+    pub installed: bool,
+    /// Output of `claude --version`
+    pub version: Option<String>,
+    /// Resolved executable path
+    pub path: Option<String>,
+}
+
+/// A directory Claude has been run in, according to its transcripts
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClaudeProject {
+    pub path: String,
+    /// Unix seconds of the most recent transcript write
+    pub last_activity: u64,
+    pub session_count: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -20,17 +105,17 @@ pub struct HooksState {
     pub hooks_json: Option<String>,
 }
 
-pub fn get_status_dir() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".woodeye-status"))
+pub fn get_status_dir() -> Result<PathBuf, String> {
+    Ok(crate::config::home_dir()?.join(".woodeye-status"))
 }
 
-fn get_names_file_path() -> Option<PathBuf> {
+fn get_names_file_path() -> Result<PathBuf, String> {
     get_status_dir().map(|d| d.join("names.json"))
 }
 
 /// Read session names from the separate names file
 fn read_session_names() -> std::collections::HashMap<String, String> {
-    let Some(path) = get_names_file_path() else {
+    let Ok(path) = get_names_file_path() else {
         return std::collections::HashMap::new();
     };
 
@@ -44,33 +129,100 @@ fn read_session_names() -> std::collections::HashMap<String, String> {
         .unwrap_or_default()
 }
 
+/// How many times to try taking the names lock, 10ms apart, before assuming
+/// its holder died and updating anyway
+const NAMES_LOCK_ATTEMPTS: u32 = 200;
+
+/// Read-modify-write the names file while holding its lock. The lock is a
+/// directory next to the file, since mkdir is atomic and, unlike flock,
+/// available to the hooks on macOS too; they take the same lock. The new
+/// contents are written to a temp file and renamed into place, so readers
+/// never see a partial file.
+fn update_names_file(
+    path: &Path,
+    update: impl FnOnce(&mut HashMap<String, String>),
+) -> Result<(), String> {
+    let lock = path.with_extension("json.lock");
+    let locked = (0..NAMES_LOCK_ATTEMPTS).any(|attempt| {
+        if attempt > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        fs::create_dir(&lock).is_ok()
+    });
+    if !locked {
+        eprintln!(
+            "Names file lock {} looks stale, updating anyway",
+            lock.display()
+        );
+    }
+
+    let result = (|| {
+        let mut names: HashMap<String, String> = fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        update(&mut names);
+
+        let updated = serde_json::to_string_pretty(&names)
+            .map_err(|e| format!("Failed to serialize names: {}", e))?;
+        let tmp = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp, updated).map_err(|e| format!("Failed to write names file: {}", e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("Failed to write names file: {}", e))
+    })();
+
+    // A lock we gave up on still belongs to someone else
+    if locked {
+        let _ = fs::remove_dir(&lock);
+    }
+    result
+}
+
+/// Set a session's display name, replacing the generated one
+pub fn set_session_name(session_id: &str, name: &str) -> Result<(), String> {
+    validate_session_id(session_id)?;
+    let path = get_names_file_path()?;
+    fs::create_dir_all(path.parent().unwrap_or(&path))
+        .map_err(|e| format!("Failed to create status directory: {}", e))?;
+    update_names_file(&path, |names| {
+        names.insert(session_id.to_string(), name.to_string());
+    })
+}
+
 /// Remove a session name from the names file
 fn remove_session_name(session_id: &str) -> Result<(), String> {
-    let path = get_names_file_path().ok_or("Could not determine names file path")?;
+    let path = get_names_file_path()?;
 
     if !path.exists() {
         return Ok(());
     }
 
-    let contents = fs::read_to_string(&path)
-        .map_err(|e| format!("Failed to read names file: {}", e))?;
-
-    let mut names: std::collections::HashMap<String, String> = serde_json::from_str(&contents)
-        .unwrap_or_default();
-
-    names.remove(session_id);
-
-    let updated = serde_json::to_string_pretty(&names)
-        .map_err(|e| format!("Failed to serialize names: {}", e))?;
+    update_names_file(&path, |names| {
+        names.remove(session_id);
+    })
+}
 
-    fs::write(&path, updated)
-        .map_err(|e| format!("Failed to write names file: {}", e))?;
+/// Parse a status file written by the hooks, skipping (with a warning) files
+/// whose schema this version doesn't understand rather than misreading them
+fn parse_status_file(contents: &str) -> Option<ClaudeSession> {
+    let session = serde_json::from_str::<ClaudeSession>(contents).ok()?;
+    if session.schema != STATUS_SCHEMA_VERSION {
+        eprintln!(
+            "Skipping status file for session {} with unsupported schema {}",
+            session.session_id, session.schema
+        );
+        return None;
+    }
+    Some(session)
+}
 
-    Ok(())
+/// Whether list_sessions reads a file in the status dir: JSON files other
+/// than the special ones (names.json, hooks_backup.json)
+fn is_status_file_name(filename: &str) -> bool {
+    filename.ends_with(".json") && filename != "names.json" && filename != "hooks_backup.json"
 }
 
 pub fn list_sessions() -> Result<Vec<ClaudeSession>, String> {
-    let status_dir = get_status_dir().ok_or("Could not determine home directory")?;
+    let status_dir = get_status_dir()?;
 
     if !status_dir.exists() {
         return Ok(Vec::new());
@@ -79,25 +231,34 @@ pub fn list_sessions() -> Result<Vec<ClaudeSession>, String> {
     // Read session names from separate file
     let names = read_session_names();
 
+    let stale_thresholds = crate::config::load_config()
+        .map(|c| c.stale_thresholds)
+        .unwrap_or_default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
     let mut sessions: Vec<ClaudeSession> = Vec::new();
 
     let entries = fs::read_dir(&status_dir).map_err(|e| format!("Failed to read status directory: {}", e))?;
 
     for entry in entries.flatten() {
         let path = entry.path();
-        // Skip non-JSON files and special files (names.json, hooks_backup.json)
         let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-        if !filename.ends_with(".json") || filename == "names.json" || filename == "hooks_backup.json" {
+        if !is_status_file_name(filename) {
             continue;
         }
 
         if let Ok(contents) = fs::read_to_string(&path) {
-            if let Ok(mut session) = serde_json::from_str::<ClaudeSession>(&contents) {
+            if let Some(mut session) = parse_status_file(&contents) {
                 // Merge name from separate names file
                 if session.name.is_none() {
                     session.name = names.get(&session.session_id).cloned();
                 }
                 session.raw_json = contents;
+                session.stale = get_stale_threshold_for_state(&session.state, &stale_thresholds)
+                    .is_some_and(|threshold| now.saturating_sub(session.timestamp) > threshold);
                 sessions.push(session);
             }
         }
@@ -109,8 +270,122 @@ pub fn list_sessions() -> Result<Vec<ClaudeSession>, String> {
     Ok(sessions)
 }
 
+/// What a file in the status dir appears to be, judging by its name
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatusFileKind {
+    /// <session_id>.json, as written by the hooks
+    SessionId,
+    /// <md5 hex>.json, the naming of older hook scripts
+    HashNamed,
+    Names,
+    HooksBackup,
+    /// Leftovers of an interrupted write or a held lock
+    Temporary,
+    Other,
+}
+
+/// One entry of the status dir and how Woodeye reads it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct StatusFileDiagnosis {
+    pub name: String,
+    pub kind: StatusFileKind,
+    /// The function that picks the file up, None when it's ignored
+    pub reader: Option<String>,
+    /// The session a status file describes, when it parses
+    pub session_id: Option<String>,
+    /// Why the file couldn't be read or parsed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatusDiagnosis {
+    pub status_dir: PathBuf,
+    pub files: Vec<StatusFileDiagnosis>,
+}
+
+/// Classify every entry of the status dir, to track down sessions that are
+/// written but never shown (or shown wrongly)
+pub fn diagnose_status_files() -> Result<StatusDiagnosis, String> {
+    let status_dir = get_status_dir()?;
+    let mut files = Vec::new();
+
+    if status_dir.exists() {
+        let entries = fs::read_dir(&status_dir)
+            .map_err(|e| format!("Failed to read status directory: {}", e))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let contents = if entry.path().is_dir() {
+                Err("Is a directory".to_string())
+            } else {
+                fs::read_to_string(entry.path()).map_err(|e| e.to_string())
+            };
+            files.push(diagnose_status_file(&name, contents));
+        }
+    }
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(StatusDiagnosis { status_dir, files })
+}
+
+fn diagnose_status_file(name: &str, contents: Result<String, String>) -> StatusFileDiagnosis {
+    let stem = name.strip_suffix(".json");
+    let kind = match name {
+        "names.json" => StatusFileKind::Names,
+        "hooks_backup.json" => StatusFileKind::HooksBackup,
+        _ if name.ends_with(".tmp") || name.ends_with(".lock") => StatusFileKind::Temporary,
+        _ => match stem {
+            Some(stem) if stem.len() == 32 && stem.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                StatusFileKind::HashNamed
+            }
+            Some(_) => StatusFileKind::SessionId,
+            None => StatusFileKind::Other,
+        },
+    };
+
+    let mut diagnosis = StatusFileDiagnosis {
+        name: name.to_string(),
+        kind,
+        reader: None,
+        session_id: None,
+        error: None,
+    };
+    match kind {
+        StatusFileKind::Names => {
+            diagnosis.reader = Some("read_session_names".to_string());
+            diagnosis.error = contents
+                .and_then(|contents| {
+                    serde_json::from_str::<HashMap<String, String>>(&contents)
+                        .map_err(|e| e.to_string())
+                })
+                .err();
+        }
+        _ if is_status_file_name(name) => {
+            diagnosis.reader = Some("list_sessions".to_string());
+            let session = contents.and_then(|contents| {
+                serde_json::from_str::<ClaudeSession>(&contents).map_err(|e| e.to_string())
+            });
+            match session {
+                Ok(session) if session.schema != STATUS_SCHEMA_VERSION => {
+                    diagnosis.error = Some(format!("Unsupported schema {}", session.schema));
+                    diagnosis.session_id = Some(session.session_id);
+                }
+                Ok(session) => {
+                    let id = session.session_id.as_str();
+                    if kind == StatusFileKind::SessionId && stem != Some(id) {
+                        diagnosis.error = Some(format!("File name doesn't match session {}", id));
+                    }
+                    diagnosis.session_id = Some(session.session_id);
+                }
+                Err(e) => diagnosis.error = Some(e),
+            }
+        }
+        _ => {}
+    }
+    diagnosis
+}
+
 pub fn delete_session(session_id: &str) -> Result<(), String> {
-    let status_dir = get_status_dir().ok_or("Could not determine home directory")?;
+    let status_dir = get_status_dir()?;
     let file_path = status_dir.join(format!("{}.json", session_id));
 
     if file_path.exists() {
@@ -124,40 +399,443 @@ pub fn delete_session(session_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Reject session ids that could escape the directory they're joined onto
+fn validate_session_id(session_id: &str) -> Result<(), String> {
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(format!("Invalid session id: {}", session_id));
+    }
+    Ok(())
+}
+
+/// Manually override a session's state, e.g. when a hook missed a transition.
+/// Other fields in the status file are preserved.
+pub fn set_session_state(session_id: &str, state: &str) -> Result<(), String> {
+    if !SESSION_STATES.contains(&state) {
+        return Err(format!("Unknown session state: {}", state));
+    }
+    validate_session_id(session_id)?;
+
+    let status_dir = get_status_dir()?;
+    let file_path = status_dir.join(format!("{}.json", session_id));
+
+    let contents = fs::read_to_string(&file_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let mut status: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse session file: {}", e))?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let obj = status
+        .as_object_mut()
+        .ok_or("Session file is not a JSON object")?;
+    obj.insert("state".to_string(), json!(state));
+    obj.insert("timestamp".to_string(), json!(timestamp));
+
+    let updated = serde_json::to_string(&status)
+        .map_err(|e| format!("Failed to serialize session: {}", e))?;
+
+    fs::write(&file_path, updated).map_err(|e| format!("Failed to write session file: {}", e))?;
+
+    Ok(())
+}
+
+/// Canonicalize a path for comparison, falling back to the path as given
+/// (e.g. when it no longer exists)
+fn normalize_path(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Whether a session's project path belongs to a worktree. The session may
+/// have been started in a subdirectory of the worktree, so the worktree only
+/// has to be an ancestor of (or equal to) the session path.
+pub fn paths_match(worktree_path: &str, session_path: &str) -> bool {
+    normalize_path(session_path).starts_with(normalize_path(worktree_path))
+}
+
+/// List the sessions running in a worktree or any of its subdirectories. With
+/// `jsonl_only`, sessions are read from Claude's transcripts instead of the
+/// hook status files, for users who don't install the hooks.
+pub fn list_sessions_for_worktree(
+    worktree_path: &str,
+    jsonl_only: bool,
+) -> Result<Vec<ClaudeSession>, String> {
+    if jsonl_only {
+        return find_sessions_from_jsonl(worktree_path);
+    }
+    Ok(list_sessions()?
+        .into_iter()
+        .filter(|session| paths_match(worktree_path, &session.project_path))
+        .collect())
+}
+
+/// Group sessions under the worktrees they belong to. When worktrees are nested,
+/// a session is attributed only to the deepest matching worktree so it isn't
+/// counted twice; if several given paths are equally deep (e.g. the same
+/// worktree listed twice), the first one wins. Sessions matching no worktree
+/// are dropped, and every worktree gets an entry even if it has no sessions.
+pub fn group_sessions_by_worktree(
+    worktree_paths: &[String],
+    sessions: Vec<ClaudeSession>,
+) -> HashMap<String, Vec<ClaudeSession>> {
+    let normalized: Vec<PathBuf> = worktree_paths.iter().map(|p| normalize_path(p)).collect();
+
+    let mut grouped: HashMap<String, Vec<ClaudeSession>> = worktree_paths
+        .iter()
+        .map(|p| (p.clone(), Vec::new()))
+        .collect();
+
+    for session in sessions {
+        let session_path = normalize_path(&session.project_path);
+        let deepest = normalized
+            .iter()
+            .enumerate()
+            .filter(|(_, worktree)| session_path.starts_with(worktree))
+            // max_by_key returns the last maximum, so compare with reversed indices
+            .max_by_key(|(i, worktree)| (worktree.components().count(), std::cmp::Reverse(*i)))
+            .map(|(i, _)| i);
+
+        if let Some(i) = deepest {
+            if let Some(group) = grouped.get_mut(&worktree_paths[i]) {
+                group.push(session);
+            }
+        }
+    }
+
+    grouped
+}
+
+/// Get the Claude sessions for each of the given worktrees
+pub fn get_all_statuses(
+    worktree_paths: &[String],
+) -> Result<HashMap<String, Vec<ClaudeSession>>, String> {
+    Ok(group_sessions_by_worktree(worktree_paths, list_sessions()?))
+}
+
+// --- Claude Logs ---
+
+/// Environment variable Claude uses to relocate its config directory
+const CLAUDE_CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// Get Claude's config directory: $CLAUDE_CONFIG_DIR if set, otherwise ~/.claude.
+/// The projects directory and settings file both resolve through here.
+pub fn get_claude_dir() -> Result<PathBuf, String> {
+    match std::env::var_os(CLAUDE_CONFIG_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        _ => Ok(crate::config::home_dir()?.join(".claude")),
+    }
+}
+
+/// Get the directory holding Claude's per-project JSONL transcripts
+fn get_claude_projects_dir() -> Result<PathBuf, String> {
+    get_claude_dir().map(|d| d.join("projects"))
+}
+
+/// Find the JSONL transcript for a session.
+/// Claude stores them as <claude dir>/projects/<encoded-project-path>/<session_id>.jsonl
+pub fn find_session_log(session_id: &str) -> Result<PathBuf, String> {
+    validate_session_id(session_id)?;
+
+    let projects_dir = get_claude_projects_dir()?;
+    let file_name = format!("{}.jsonl", session_id);
+
+    let entries = fs::read_dir(&projects_dir)
+        .map_err(|e| format!("Failed to read Claude projects directory: {}", e))?;
+
+    entries
+        .flatten()
+        .map(|entry| entry.path().join(&file_name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("No log found for session {}", session_id))
+}
+
+/// Transcripts not written to for this long are treated as ended sessions
+const JSONL_ACTIVE_SECS: u64 = 3600;
+
+/// How much of the end of a transcript to read when looking for its last entry
+const JSONL_TAIL_BYTES: u64 = 64 * 1024;
+
+/// Encode a project path the way Claude names its projects directories:
+/// every character other than an ASCII letter or digit becomes '-'
+fn encode_project_dir(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Read the last user or assistant entry of a JSONL transcript, skipping
+/// summaries and other bookkeeping lines
+fn read_last_transcript_entry(path: &Path) -> Option<Value> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(JSONL_TAIL_BYTES)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+
+    // The first line may be cut off mid-entry, in which case it won't parse
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .find(|entry| matches!(entry["type"].as_str(), Some("user" | "assistant")))
+}
+
+/// Infer a session state from its last transcript entry. Transcripts can't
+/// show a pending permission prompt, so a session waiting for approval reads
+/// as working.
+fn transcript_state(entry: &Value) -> &'static str {
+    if entry["type"] == "user" {
+        // A prompt or tool result that Claude is responding to
+        return "working";
+    }
+    let uses_tool = entry["message"]["content"]
+        .as_array()
+        .is_some_and(|blocks| blocks.iter().any(|b| b["type"] == "tool_use"));
+    if uses_tool {
+        "working"
+    } else {
+        "idle"
+    }
+}
+
+/// Find a worktree's recently active sessions from Claude's JSONL transcripts.
+/// This works without the hooks, but state resolution is coarser: only
+/// working and idle are distinguished, and the timestamp is the transcript's
+/// last write.
+pub fn find_sessions_from_jsonl(worktree_path: &str) -> Result<Vec<ClaudeSession>, String> {
+    let projects_dir = get_claude_projects_dir()?;
+    if !projects_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    // Sessions started in a subdirectory live in a directory with a longer name
+    let worktree_dir = encode_project_dir(&normalize_path(worktree_path).to_string_lossy());
+    let names = read_session_names();
+    let stale_thresholds = crate::config::load_config()
+        .map(|c| c.stale_thresholds)
+        .unwrap_or_default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut sessions: Vec<ClaudeSession> = Vec::new();
+
+    let entries = fs::read_dir(&projects_dir)
+        .map_err(|e| format!("Failed to read Claude projects directory: {}", e))?;
+
+    for project in entries.flatten() {
+        let dir_name = project.file_name().to_string_lossy().to_string();
+        if !dir_name.starts_with(&worktree_dir) {
+            continue;
+        }
+        let Ok(logs) = fs::read_dir(project.path()) else {
+            continue;
+        };
+
+        for log in logs.flatten() {
+            let path = log.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let Some(timestamp) = log
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+            else {
+                continue;
+            };
+            if now.saturating_sub(timestamp) > JSONL_ACTIVE_SECS {
+                continue;
+            }
+
+            let Some(entry) = read_last_transcript_entry(&path) else {
+                continue;
+            };
+            // The encoding is lossy, so confirm the session really ran in the worktree
+            let Some(project_path) = entry["cwd"].as_str() else {
+                continue;
+            };
+            if !paths_match(worktree_path, project_path) {
+                continue;
+            }
+
+            let session_id = entry["sessionId"]
+                .as_str()
+                .map(str::to_string)
+                .or_else(|| Some(path.file_stem()?.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            let state = transcript_state(&entry);
+            sessions.push(ClaudeSession {
+                schema: STATUS_SCHEMA_VERSION,
+                project_path: project_path.to_string(),
+                name: names.get(&session_id).cloned(),
+                session_id,
+                state: state.to_string(),
+                timestamp,
+                raw_json: entry.to_string(),
+                stale: get_stale_threshold_for_state(state, &stale_thresholds)
+                    .is_some_and(|threshold| now.saturating_sub(timestamp) > threshold),
+            });
+        }
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+
+    Ok(sessions)
+}
+
+/// How many lines to read from the start of a transcript looking for its cwd
+const JSONL_CWD_SCAN_LINES: usize = 20;
+
+/// Read the working directory from the first transcript entry that has one
+fn read_transcript_cwd(path: &Path) -> Option<String> {
+    use std::io::{BufRead, BufReader};
+
+    let file = fs::File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(JSONL_CWD_SCAN_LINES)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+        .find_map(|entry| entry["cwd"].as_str().map(str::to_string))
+}
+
+/// Combine (cwd, last write) pairs of transcripts into projects, most
+/// recently active first
+fn collect_claude_projects(logs: impl IntoIterator<Item = (String, u64)>) -> Vec<ClaudeProject> {
+    let mut projects: HashMap<String, ClaudeProject> = HashMap::new();
+    for (path, timestamp) in logs {
+        let project = projects.entry(path.clone()).or_insert(ClaudeProject {
+            path,
+            last_activity: 0,
+            session_count: 0,
+        });
+        project.last_activity = project.last_activity.max(timestamp);
+        project.session_count += 1;
+    }
+
+    let mut projects: Vec<ClaudeProject> = projects.into_values().collect();
+    projects.sort_by(|a, b| {
+        b.last_activity
+            .cmp(&a.last_activity)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    projects
+}
+
+/// List every directory Claude has transcripts for, whether or not it is a
+/// worktree open in Woodeye
+pub fn list_claude_projects() -> Result<Vec<ClaudeProject>, String> {
+    let projects_dir = get_claude_projects_dir()?;
+    if !projects_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&projects_dir)
+        .map_err(|e| format!("Failed to read Claude projects directory: {}", e))?;
+
+    let mut logs: Vec<(String, u64)> = Vec::new();
+    for project in entries.flatten() {
+        let Ok(project_logs) = fs::read_dir(project.path()) else {
+            continue;
+        };
+        for log in project_logs.flatten() {
+            let path = log.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                continue;
+            }
+            let timestamp = log
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            if let Some(cwd) = read_transcript_cwd(&path) {
+                logs.push((cwd, timestamp));
+            }
+        }
+    }
+
+    Ok(collect_claude_projects(logs))
+}
+
+// --- Claude CLI ---
+
+/// Run a command and return its trimmed stdout if it succeeded with output
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = crate::shell_env::command(program)
+        .args(args)
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !stdout.is_empty()).then_some(stdout)
+}
+
+/// Check whether `claude` is on the PATH Woodeye spawns processes with, which
+/// for GUI-launched apps can be narrower than the user's shell PATH
+pub fn check_claude_cli() -> ClaudeCliInfo {
+    let version = command_stdout("claude", &["--version"]);
+    let path = command_stdout("sh", &["-c", "command -v claude"]);
+    ClaudeCliInfo {
+        installed: version.is_some() || path.is_some(),
+        version,
+        path,
+    }
+}
+
 // --- Hooks Management ---
 
-fn get_claude_settings_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|h| h.join(".claude").join("settings.json"))
+fn get_claude_settings_path() -> Result<PathBuf, String> {
+    get_claude_dir().map(|d| d.join("settings.json"))
 }
 
-fn get_hooks_backup_path() -> Option<PathBuf> {
+fn get_hooks_backup_path() -> Result<PathBuf, String> {
     get_status_dir().map(|d| d.join("hooks_backup.json"))
 }
 
-/// Generate the Woodeye status hooks configuration
-fn generate_woodeye_hooks() -> Value {
-    let status_dir = get_status_dir()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| "/tmp/.woodeye-status".to_string());
+/// Shell snippet applying a jq filter to the names file at $nf under the same
+/// lock and temp-file-and-rename scheme as update_names_file
+fn names_update_cmd(jq_args: &str) -> String {
+    format!(
+        r#"l=0; i=0; while [ $i -lt {} ]; do mkdir "$nf.lock" 2>/dev/null && {{ l=1; break; }}; i=$((i+1)); sleep 0.01; done; {{ cat "$nf" 2>/dev/null || echo '{{}}'; }} | jq {} > "$nf.$$.tmp" && mv "$nf.$$.tmp" "$nf"; if [ $l = 1 ]; then rmdir "$nf.lock" 2>/dev/null; fi"#,
+        NAMES_LOCK_ATTEMPTS, jq_args
+    )
+}
 
+/// Generate the Woodeye status hooks configuration, writing to `status_dir`
+fn generate_woodeye_hooks(status_dir: &str) -> Value {
     let base_cmd = |state: &str| -> String {
         format!(
-            r#"[ -n "$WOODEYE_HOOK" ] && exit 0; input=$(cat); sid=$(echo "$input" | jq -r '.session_id'); [ -n "$CLAUDE_PROJECT_DIR" ] && [ -n "$sid" ] && mkdir -p {} && echo "{{\"project_path\":\"$CLAUDE_PROJECT_DIR\",\"session_id\":\"$sid\",\"state\":\"{}\",\"timestamp\":$(date +%s)}}" > {}/{{}}.json"#,
-            status_dir, state, status_dir
+            r#"[ -n "$WOODEYE_HOOK" ] && exit 0; input=$(cat); sid=$(echo "$input" | jq -r '.session_id'); [ -n "$CLAUDE_PROJECT_DIR" ] && [ -n "$sid" ] && mkdir -p {} && echo "{{\"project_path\":\"$CLAUDE_PROJECT_DIR\",\"session_id\":\"$sid\",\"state\":\"{}\",\"timestamp\":$(date +%s),\"schema\":{}}}" > {}/{{}}.json"#,
+            status_dir, state, STATUS_SCHEMA_VERSION, status_dir
         ).replace("{}", "$sid")
     };
 
     let cleanup_cmd = format!(
-        r#"[ -n "$WOODEYE_HOOK" ] && exit 0; input=$(cat); sid=$(echo "$input" | jq -r '.session_id'); if [ -n "$sid" ]; then rm -f {0}/"$sid".json; nf="{0}/names.json"; if [ -f "$nf" ]; then jq --arg s "$sid" 'del(.[$s])' "$nf" > "$nf.tmp" && mv "$nf.tmp" "$nf"; fi; fi"#,
-        status_dir
+        r#"[ -n "$WOODEYE_HOOK" ] && exit 0; input=$(cat); sid=$(echo "$input" | jq -r '.session_id'); if [ -n "$sid" ]; then rm -f {0}/"$sid".json; nf="{0}/names.json"; if [ -f "$nf" ]; then {1}; fi; fi"#,
+        status_dir,
+        names_update_cmd(r#"--arg s "$sid" 'del(.[$s])'"#)
     );
 
     // Command to generate session name using Claude CLI for smart naming
     // Uses git branch name + prompt to generate a concise session title
     // Updates on every prompt, runs async (backgrounded) with timeout to avoid blocking
+    // Names are truncated by jq, which counts characters rather than bytes, so
+    // multibyte characters are never split
     let name_cmd = format!(
-        r#"input=$(cat); (sid=$(echo "$input" | jq -r '.session_id'); prompt=$(echo "$input" | jq -r '.prompt // empty'); nf="{0}/names.json"; if [ -n "$sid" ] && [ -n "$prompt" ]; then branch=""; if [ -n "$CLAUDE_PROJECT_DIR" ] && [ -d "$CLAUDE_PROJECT_DIR/.git" ]; then branch=$(git -C "$CLAUDE_PROJECT_DIR" rev-parse --abbrev-ref HEAD 2>/dev/null); fi; context="User prompt: $prompt"; if [ -n "$branch" ]; then context="Git branch: $branch\n$context"; fi; name=$(WOODEYE_HOOK=1 timeout 10 claude -p "Create a brief 3-5 word title for this coding session. Be specific about the task. No quotes, colons, or extra punctuation. Just output the title:\n$context" --model sonnet 2>/dev/null | tr -d '\n' | head -c 50); if [ -z "$name" ]; then name=$(printf '%s' "$prompt" | head -c 50 | sed 's/[[:space:]][^[:space:]]*$//'); fi; if [ -f "$nf" ]; then jq --arg s "$sid" --arg n "$name" '. + {{($s): $n}}' "$nf" > "$nf.tmp" && mv "$nf.tmp" "$nf"; else echo "{{\"$sid\":\"$name\"}}" > "$nf"; fi; fi) &"#,
-        status_dir
+        r#"input=$(cat); (sid=$(echo "$input" | jq -r '.session_id'); prompt=$(echo "$input" | jq -r '.prompt // empty'); nf="{0}/names.json"; if [ -n "$sid" ] && [ -n "$prompt" ]; then branch=""; if [ -n "$CLAUDE_PROJECT_DIR" ] && [ -d "$CLAUDE_PROJECT_DIR/.git" ]; then branch=$(git -C "$CLAUDE_PROJECT_DIR" rev-parse --abbrev-ref HEAD 2>/dev/null); fi; context="User prompt: $prompt"; if [ -n "$branch" ]; then context="Git branch: $branch\n$context"; fi; name=$(WOODEYE_HOOK=1 timeout 10 claude -p "Create a brief 3-5 word title for this coding session. Be specific about the task. No quotes, colons, or extra punctuation. Just output the title:\n$context" --model sonnet 2>/dev/null | tr -d '\n' | jq -Rrs '.[0:50]'); if [ -z "$name" ]; then name=$(printf '%s' "$prompt" | jq -Rrs 'if length > 50 then .[0:50] | sub("\\s+\\S*$"; "") else . end'); fi; {1}; fi) &"#,
+        status_dir,
+        names_update_cmd(r#"--arg s "$sid" --arg n "$name" '. + {($s): $n}'"#)
     );
 
     json!({
@@ -215,10 +893,79 @@ fn generate_woodeye_hooks() -> Value {
     })
 }
 
+/// Whether a hook entry was generated by Woodeye (its commands reference the status dir)
+fn is_woodeye_hook_entry(entry: &Value, status_dir: &str) -> bool {
+    entry
+        .get("hooks")
+        .and_then(|h| h.as_array())
+        .is_some_and(|hooks| {
+            hooks.iter().any(|hook| {
+                hook.get("command")
+                    .and_then(|c| c.as_str())
+                    .is_some_and(|c| c.contains(status_dir))
+            })
+        })
+}
+
+/// Merge Woodeye's hooks into the user's existing hooks. Previous Woodeye
+/// entries are replaced; the user's own hooks and events are preserved.
+fn merge_hooks(existing: Option<&Value>, woodeye_hooks: Value, status_dir: &str) -> Value {
+    let mut merged = existing
+        .and_then(|h| h.as_object())
+        .cloned()
+        .unwrap_or_default();
+
+    if let Value::Object(woodeye_events) = woodeye_hooks {
+        for (event, woodeye_entries) in woodeye_events {
+            let mut entries: Vec<Value> = merged
+                .get(&event)
+                .and_then(|e| e.as_array())
+                .map(|entries| {
+                    entries
+                        .iter()
+                        .filter(|entry| !is_woodeye_hook_entry(entry, status_dir))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            if let Value::Array(new_entries) = woodeye_entries {
+                entries.extend(new_entries);
+            }
+
+            merged.insert(event, Value::Array(entries));
+        }
+    }
+
+    Value::Object(merged)
+}
+
+/// Drop hook events that aren't in `supported_events` (None keeps every event).
+/// Returns the remaining hooks and the names of the skipped events.
+fn filter_supported_events(
+    hooks: Value,
+    supported_events: Option<&[String]>,
+) -> (Value, Vec<String>) {
+    let (Some(supported), Value::Object(events)) = (supported_events, &hooks) else {
+        return (hooks, Vec::new());
+    };
+
+    let mut kept = serde_json::Map::new();
+    let mut skipped = Vec::new();
+    for (event, entries) in events {
+        if supported.contains(event) {
+            kept.insert(event.clone(), entries.clone());
+        } else {
+            skipped.push(event.clone());
+        }
+    }
+
+    (Value::Object(kept), skipped)
+}
+
 /// Check if Woodeye hooks are currently enabled in Claude settings
 pub fn get_hooks_state() -> Result<HooksState, String> {
-    let settings_path = get_claude_settings_path()
-        .ok_or("Could not determine Claude settings path")?;
+    let settings_path = get_claude_settings_path()?;
 
     if !settings_path.exists() {
         return Ok(HooksState {
@@ -248,8 +995,7 @@ pub fn get_hooks_state() -> Result<HooksState, String> {
 
 /// Remove Woodeye hooks from Claude settings (backs up first)
 pub fn remove_hooks() -> Result<(), String> {
-    let settings_path = get_claude_settings_path()
-        .ok_or("Could not determine Claude settings path")?;
+    let settings_path = get_claude_settings_path()?;
 
     if !settings_path.exists() {
         return Ok(());
@@ -263,8 +1009,7 @@ pub fn remove_hooks() -> Result<(), String> {
 
     // Backup current hooks if they exist
     if let Some(hooks) = settings.get("hooks") {
-        let backup_path = get_hooks_backup_path()
-            .ok_or("Could not determine hooks backup path")?;
+        let backup_path = get_hooks_backup_path()?;
 
         // Ensure status dir exists
         if let Some(parent) = backup_path.parent() {
@@ -294,10 +1039,11 @@ pub fn remove_hooks() -> Result<(), String> {
     Ok(())
 }
 
-/// Apply Woodeye hooks to Claude settings
-pub fn apply_hooks() -> Result<(), String> {
-    let settings_path = get_claude_settings_path()
-        .ok_or("Could not determine Claude settings path")?;
+/// Apply Woodeye hooks to Claude settings, merging with any existing hooks.
+/// When `supported_events` is given, only hooks for those events are written
+/// and Woodeye hooks previously written for other events are removed.
+pub fn apply_hooks(supported_events: Option<&[String]>) -> Result<ApplyHooksResult, String> {
+    let settings_path = get_claude_settings_path()?;
 
     // Read existing settings or create new
     let mut settings: Value = if settings_path.exists() {
@@ -314,8 +1060,23 @@ pub fn apply_hooks() -> Result<(), String> {
         json!({})
     };
 
-    // Generate and apply hooks
-    let hooks = generate_woodeye_hooks();
+    // Generate and merge hooks, keeping any hooks the user configured themselves
+    let status_dir = get_status_dir()?.to_string_lossy().to_string();
+    let (woodeye_hooks, skipped_events) =
+        filter_supported_events(generate_woodeye_hooks(&status_dir), supported_events);
+    let mut hooks = merge_hooks(settings.get("hooks"), woodeye_hooks, &status_dir);
+
+    if let Some(events) = hooks.as_object_mut() {
+        for event in &skipped_events {
+            let Some(Value::Array(entries)) = events.get_mut(event) else {
+                continue;
+            };
+            entries.retain(|entry| !is_woodeye_hook_entry(entry, &status_dir));
+            if entries.is_empty() {
+                events.remove(event);
+            }
+        }
+    }
 
     if let Some(obj) = settings.as_object_mut() {
         obj.insert("hooks".to_string(), hooks);
@@ -328,5 +1089,518 @@ pub fn apply_hooks() -> Result<(), String> {
     fs::write(&settings_path, updated)
         .map_err(|e| format!("Failed to write settings: {}", e))?;
 
-    Ok(())
+    Ok(ApplyHooksResult { skipped_events })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS_DIR: &str = "/home/me/.woodeye-status";
+
+    #[test]
+    fn test_diagnose_status_file() {
+        let status = |sid: &str, schema: u32| {
+            Ok(format!(
+                r#"{{"project_path":"/src/repo","session_id":"{}","state":"idle","timestamp":1,"schema":{}}}"#,
+                sid, schema
+            ))
+        };
+
+        let ok = diagnose_status_file("abc-123.json", status("abc-123", STATUS_SCHEMA_VERSION));
+        assert_eq!(ok.kind, StatusFileKind::SessionId);
+        assert_eq!(ok.reader.as_deref(), Some("list_sessions"));
+        assert_eq!(ok.session_id.as_deref(), Some("abc-123"));
+        assert_eq!(ok.error, None);
+
+        let hashed = diagnose_status_file(
+            "0123456789abcdef0123456789abcdef.json",
+            status("abc-123", STATUS_SCHEMA_VERSION),
+        );
+        assert_eq!(hashed.kind, StatusFileKind::HashNamed);
+        assert_eq!(hashed.session_id.as_deref(), Some("abc-123"));
+
+        let old = diagnose_status_file("abc-123.json", status("abc-123", 99));
+        assert!(old.error.unwrap().contains("schema"));
+
+        let garbage = diagnose_status_file("abc-123.json", Ok("{".to_string()));
+        assert!(garbage.error.is_some());
+        assert_eq!(garbage.session_id, None);
+
+        let names =
+            diagnose_status_file("names.json", Ok(r#"{"abc-123":"Fix login"}"#.to_string()));
+        assert_eq!(names.kind, StatusFileKind::Names);
+        assert_eq!(names.reader.as_deref(), Some("read_session_names"));
+        assert_eq!(names.error, None);
+
+        let backup = diagnose_status_file("hooks_backup.json", Ok("{}".to_string()));
+        assert_eq!(backup.kind, StatusFileKind::HooksBackup);
+        assert_eq!(backup.reader, None);
+
+        let tmp = diagnose_status_file("names.json.42.tmp", Ok(String::new()));
+        assert_eq!(tmp.kind, StatusFileKind::Temporary);
+        assert_eq!(tmp.reader, None);
+    }
+
+    #[test]
+    fn test_claude_dir_honors_config_dir_env() {
+        // The only test that touches this variable, so it can't race with others
+        std::env::set_var(CLAUDE_CONFIG_DIR_ENV, "/opt/claude-config");
+        let claude_dir = get_claude_dir();
+        let settings_path = get_claude_settings_path();
+        let projects_dir = get_claude_projects_dir();
+        std::env::remove_var(CLAUDE_CONFIG_DIR_ENV);
+
+        assert_eq!(claude_dir, Ok(PathBuf::from("/opt/claude-config")));
+        assert_eq!(
+            settings_path,
+            Ok(PathBuf::from("/opt/claude-config/settings.json"))
+        );
+        assert_eq!(
+            projects_dir,
+            Ok(PathBuf::from("/opt/claude-config/projects"))
+        );
+    }
+
+    fn woodeye_entry() -> Value {
+        json!({"hooks": [{"command": format!("echo > {}/x.json", STATUS_DIR), "type": "command"}]})
+    }
+
+    #[test]
+    fn test_paths_match_exact() {
+        assert!(paths_match("/nonexistent/repo", "/nonexistent/repo"));
+    }
+
+    #[test]
+    fn test_paths_match_subdirectory() {
+        assert!(paths_match("/nonexistent/repo", "/nonexistent/repo/src/ui"));
+        assert!(!paths_match("/nonexistent/repo/src", "/nonexistent/repo"));
+    }
+
+    #[test]
+    fn test_paths_match_sibling_prefix() {
+        // A shared string prefix is not an ancestor
+        assert!(!paths_match(
+            "/nonexistent/repo",
+            "/nonexistent/repo-feature"
+        ));
+    }
+
+    #[test]
+    fn test_paths_match_canonicalizes() {
+        let dir = std::env::temp_dir().join(format!("woodeye-paths-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        let dir_str = dir.to_string_lossy().to_string();
+
+        let session_path = format!("{}/sub/../sub", dir_str);
+        assert!(paths_match(&format!("{}/", dir_str), &session_path));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn session(id: &str, project_path: &str) -> ClaudeSession {
+        ClaudeSession {
+            schema: STATUS_SCHEMA_VERSION,
+            project_path: project_path.to_string(),
+            session_id: id.to_string(),
+            state: "working".to_string(),
+            timestamp: 0,
+            name: None,
+            raw_json: String::new(),
+            stale: false,
+        }
+    }
+
+    #[test]
+    fn test_group_sessions_by_worktree_prefers_deepest() {
+        let worktrees = vec![
+            "/nonexistent/repo".to_string(),
+            "/nonexistent/repo/nested".to_string(),
+        ];
+        let sessions = vec![
+            session("a", "/nonexistent/repo/src"),
+            session("b", "/nonexistent/repo/nested/src"),
+            session("c", "/nonexistent/elsewhere"),
+        ];
+
+        let grouped = group_sessions_by_worktree(&worktrees, sessions);
+
+        let ids = |path: &str| -> Vec<String> {
+            grouped[path].iter().map(|s| s.session_id.clone()).collect()
+        };
+        assert_eq!(ids("/nonexistent/repo"), vec!["a"]);
+        assert_eq!(ids("/nonexistent/repo/nested"), vec!["b"]);
+    }
+
+    #[test]
+    fn test_group_sessions_by_worktree_tie_goes_to_first() {
+        let worktrees = vec![
+            "/nonexistent/repo".to_string(),
+            "/nonexistent/repo/".to_string(),
+        ];
+        let grouped =
+            group_sessions_by_worktree(&worktrees, vec![session("a", "/nonexistent/repo")]);
+
+        assert_eq!(grouped["/nonexistent/repo"].len(), 1);
+        assert!(grouped["/nonexistent/repo/"].is_empty());
+    }
+
+    #[test]
+    fn test_stale_threshold_defaults() {
+        let configured = HashMap::new();
+        assert_eq!(
+            get_stale_threshold_for_state("waiting_for_approval", &configured),
+            Some(DEFAULT_STALE_THRESHOLD_SECS)
+        );
+        assert_eq!(
+            get_stale_threshold_for_state("idle", &configured),
+            Some(DEFAULT_STALE_THRESHOLD_SECS)
+        );
+        assert_eq!(get_stale_threshold_for_state("working", &configured), None);
+    }
+
+    #[test]
+    fn test_stale_threshold_configured_per_state() {
+        let configured = HashMap::from([
+            ("idle".to_string(), 86400),
+            ("waiting_for_approval".to_string(), 120),
+        ]);
+        assert_eq!(
+            get_stale_threshold_for_state("idle", &configured),
+            Some(86400)
+        );
+        assert_eq!(
+            get_stale_threshold_for_state("waiting_for_approval", &configured),
+            Some(120)
+        );
+    }
+
+    #[test]
+    fn test_stale_threshold_working_only_when_configured() {
+        let configured = HashMap::from([("working".to_string(), 3600)]);
+        assert_eq!(
+            get_stale_threshold_for_state("working", &configured),
+            Some(3600)
+        );
+        // Overriding one state leaves the others on their defaults
+        assert_eq!(
+            get_stale_threshold_for_state("idle", &configured),
+            Some(DEFAULT_STALE_THRESHOLD_SECS)
+        );
+    }
+
+    #[test]
+    fn test_stale_threshold_other_waiting_states() {
+        let configured = HashMap::from([("waiting_for_approval".to_string(), 120)]);
+        // Any waiting state defaults like waiting_for_approval, but overrides are per exact state
+        assert_eq!(
+            get_stale_threshold_for_state("waiting_for_input", &configured),
+            Some(DEFAULT_STALE_THRESHOLD_SECS)
+        );
+    }
+
+    #[test]
+    fn test_stale_threshold_unknown_state() {
+        let configured = HashMap::from([("compacting".to_string(), 60)]);
+        assert_eq!(
+            get_stale_threshold_for_state("compacting", &configured),
+            Some(60)
+        );
+        assert_eq!(
+            get_stale_threshold_for_state("compacting", &HashMap::new()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_status_file_without_schema() {
+        let contents = r#"{"project_path":"/repo","session_id":"a","state":"idle","timestamp":1}"#;
+        assert_eq!(parse_status_file(contents).unwrap().schema, 1);
+    }
+
+    #[test]
+    fn test_parse_status_file_rejects_unknown_schema() {
+        let contents =
+            r#"{"project_path":"/repo","session_id":"a","state":"idle","timestamp":1,"schema":99}"#;
+        assert!(parse_status_file(contents).is_none());
+    }
+
+    #[test]
+    fn test_filter_supported_events() {
+        let hooks = json!({"Stop": [woodeye_entry()], "OldEvent": [woodeye_entry()]});
+
+        let (kept, skipped) = filter_supported_events(hooks.clone(), None);
+        assert_eq!(kept, hooks);
+        assert!(skipped.is_empty());
+
+        let supported = vec!["Stop".to_string()];
+        let (kept, skipped) = filter_supported_events(hooks, Some(&supported));
+        assert_eq!(kept, json!({"Stop": [woodeye_entry()]}));
+        assert_eq!(skipped, vec!["OldEvent"]);
+    }
+
+    #[test]
+    fn test_merge_hooks_into_empty() {
+        let merged = merge_hooks(None, json!({"Stop": [woodeye_entry()]}), STATUS_DIR);
+        assert_eq!(merged, json!({"Stop": [woodeye_entry()]}));
+    }
+
+    #[test]
+    fn test_merge_hooks_preserves_user_hooks() {
+        let user_entry = json!({"hooks": [{"command": "say done", "type": "command"}]});
+        let existing = json!({
+            "Stop": [user_entry.clone(), woodeye_entry()],
+            "PreCompact": [user_entry.clone()]
+        });
+
+        let merged = merge_hooks(
+            Some(&existing),
+            json!({"Stop": [woodeye_entry()]}),
+            STATUS_DIR,
+        );
+
+        // The old Woodeye entry is replaced rather than duplicated
+        assert_eq!(merged["Stop"], json!([user_entry.clone(), woodeye_entry()]));
+        assert_eq!(merged["PreCompact"], json!([user_entry]));
+    }
+
+    #[test]
+    fn test_encode_project_dir() {
+        assert_eq!(
+            encode_project_dir("/Users/me/my_project.v2"),
+            "-Users-me-my-project-v2"
+        );
+    }
+
+    #[test]
+    fn test_transcript_state() {
+        let prompt = json!({"type": "user", "message": {"role": "user", "content": "Fix it"}});
+        let tool_call = json!({"type": "assistant", "message": {"content": [
+            {"type": "text", "text": "Running tests"},
+            {"type": "tool_use", "name": "Bash"}
+        ]}});
+        let reply = json!({"type": "assistant", "message": {"content": [
+            {"type": "text", "text": "Done"}
+        ]}});
+
+        assert_eq!(transcript_state(&prompt), "working");
+        assert_eq!(transcript_state(&tool_call), "working");
+        assert_eq!(transcript_state(&reply), "idle");
+    }
+
+    #[test]
+    fn test_read_last_transcript_entry_skips_bookkeeping() {
+        let path = std::env::temp_dir().join(format!(
+            "woodeye-transcript-test-{}.jsonl",
+            std::process::id()
+        ));
+        let lines = [
+            json!({"type": "user", "sessionId": "abc", "cwd": "/repo"}),
+            json!({"type": "assistant", "sessionId": "abc", "cwd": "/repo"}),
+            json!({"type": "summary", "summary": "Fixing tests"}),
+        ];
+        let contents: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        fs::write(&path, contents).unwrap();
+
+        let entry = read_last_transcript_entry(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(entry, Some(lines[1].clone()));
+    }
+
+    #[test]
+    fn test_read_transcript_cwd_skips_entries_without_one() {
+        let path = std::env::temp_dir().join(format!(
+            "woodeye-transcript-cwd-test-{}.jsonl",
+            std::process::id()
+        ));
+        let lines = [
+            json!({"type": "summary", "summary": "Fixing tests"}),
+            json!({"type": "user", "sessionId": "abc", "cwd": "/repo"}),
+        ];
+        let contents: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+        fs::write(&path, contents).unwrap();
+
+        let cwd = read_transcript_cwd(&path);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(cwd.as_deref(), Some("/repo"));
+    }
+
+    #[test]
+    fn test_collect_claude_projects() {
+        let projects = collect_claude_projects([
+            ("/repo".to_string(), 100),
+            ("/other".to_string(), 300),
+            ("/repo".to_string(), 200),
+        ]);
+        assert_eq!(
+            projects,
+            vec![
+                ClaudeProject {
+                    path: "/other".to_string(),
+                    last_activity: 300,
+                    session_count: 1,
+                },
+                ClaudeProject {
+                    path: "/repo".to_string(),
+                    last_activity: 200,
+                    session_count: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_names_file_concurrent() {
+        let dir = std::env::temp_dir().join(format!("woodeye-names-stress-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("names.json");
+
+        std::thread::scope(|scope| {
+            for thread in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    for i in 0..20 {
+                        update_names_file(path, |names| {
+                            names.insert(format!("{}-{}", thread, i), "name".to_string());
+                        })
+                        .unwrap();
+                    }
+                });
+            }
+        });
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lock_left = path.with_extension("json.lock").exists();
+        let _ = fs::remove_dir_all(&dir);
+        let names: HashMap<String, String> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(names.len(), 160);
+        assert!(!lock_left);
+    }
+
+    #[test]
+    fn test_update_names_file_keeps_foreign_lock() {
+        let dir = std::env::temp_dir().join(format!("woodeye-names-stale-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("names.json");
+        let lock = path.with_extension("json.lock");
+        fs::create_dir(&lock).unwrap();
+
+        update_names_file(&path, |names| {
+            names.insert("s".to_string(), "name".to_string());
+        })
+        .unwrap();
+
+        let lock_left = lock.exists();
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        let names: HashMap<String, String> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(names["s"], "name");
+        assert!(lock_left);
+    }
+
+    /// Run the UserPromptSubmit hook with a stub `claude` that prints `title`
+    fn run_name_hook(status_dir: &Path, session_id: &str, prompt: &str, title: &str) {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+        use std::process::{Command, Stdio};
+
+        let bin_dir = status_dir.join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        let claude = bin_dir.join("claude");
+        fs::write(&claude, format!("#!/bin/sh\nprintf '%s' '{}'\n", title)).unwrap();
+        fs::set_permissions(&claude, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let hooks = generate_woodeye_hooks(&status_dir.to_string_lossy());
+        let name_cmd = hooks["UserPromptSubmit"][0]["hooks"][0]["command"]
+            .as_str()
+            .unwrap();
+        let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap());
+
+        // The hook backgrounds its work, so wait for it before returning
+        let mut child = Command::new("sh")
+            .args(["-c", &format!("{}\nwait", name_cmd)])
+            .env("PATH", path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = json!({"session_id": session_id, "prompt": prompt});
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.to_string().as_bytes())
+            .unwrap();
+        assert!(child.wait().unwrap().success());
+    }
+
+    #[test]
+    fn test_name_hook_truncates_multibyte_names() {
+        use std::process::Command;
+
+        // The hook relies on jq, like the rest of the status hooks
+        if Command::new("jq").arg("--version").output().is_err() {
+            return;
+        }
+
+        let status_dir =
+            std::env::temp_dir().join(format!("woodeye-names-test-{}", std::process::id()));
+        fs::create_dir_all(&status_dir).unwrap();
+
+        // 4-byte emoji, so truncating at 50 bytes would split one
+        let long_title = "🚀".repeat(60);
+        run_name_hook(&status_dir, "titled", "Fix it", &long_title);
+        // Without a title the prompt is used, cut back to the last whole word
+        let prompt = "Fix the 🚀 launch ✨ sequence so every 🐛 bug is squashed before the release";
+        run_name_hook(&status_dir, "untitled", prompt, "");
+
+        let contents = fs::read(status_dir.join("names.json")).unwrap();
+        let _ = fs::remove_dir_all(&status_dir);
+        let names: HashMap<String, String> = serde_json::from_slice(&contents).unwrap();
+
+        assert_eq!(names["titled"], "🚀".repeat(50));
+        assert_eq!(
+            names["untitled"],
+            "Fix the 🚀 launch ✨ sequence so every 🐛 bug is"
+        );
+    }
+
+    #[test]
+    fn test_name_hooks_concurrent_with_app_updates() {
+        use std::process::Command;
+
+        if Command::new("jq").arg("--version").output().is_err() {
+            return;
+        }
+
+        let status_dir =
+            std::env::temp_dir().join(format!("woodeye-names-race-{}", std::process::id()));
+        fs::create_dir_all(&status_dir).unwrap();
+        let path = status_dir.join("names.json");
+
+        std::thread::scope(|scope| {
+            for i in 0..6 {
+                let status_dir = &status_dir;
+                scope.spawn(move || {
+                    let sid = format!("hook-{}", i);
+                    run_name_hook(status_dir, &sid, "Fix it", "Hook title");
+                });
+            }
+            for i in 0..6 {
+                let path = &path;
+                scope.spawn(move || {
+                    update_names_file(path, |names| {
+                        names.insert(format!("app-{}", i), "App title".to_string());
+                    })
+                    .unwrap();
+                });
+            }
+        });
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_dir_all(&status_dir);
+        let names: HashMap<String, String> = serde_json::from_str(&contents).unwrap();
+        assert_eq!(names.len(), 12);
+    }
 }
@@ -1,14 +1,28 @@
-use crate::claude_status::{self, ClaudeSession, HooksState};
-use crate::config::{self, WoodeyeConfig};
+use crate::claude_status::{
+    self, ApplyHooksResult, ClaudeCliInfo, ClaudeProject, ClaudeSession, HooksState,
+    StatusDiagnosis, WorktreeClaudeStatus,
+};
+use crate::config::{self, ActionDef, ActionKind, EffectiveConfig, WindowLevel, WoodeyeConfig};
 use crate::git;
+use crate::history::{self, SessionHistoryDay};
 use crate::menu;
+use crate::shell_env;
+use crate::terminal;
 use crate::types::{
-    BranchInfo, CommitDiff, CommitInfo, CreateWorktreeOptions, PruneResult, WorkingDiff, Worktree,
-    WorktreeStatus,
+    ApplyResult, BranchExistence, BranchInfo, BranchesContaining, ChangeCounts, CleanState,
+    CommitDiff, CommitInfo, ConflictFile, CreateOutcome, CreateWorktreeOptions, DayActivity,
+    DeleteOutcome, DeleteWorktreeResult, DiffStats, FileBlob, FileDiff, GcResult, GitDirs,
+    GitProgress, Identity, LfsFile, OperationState, PruneResult, RebaseAdvice, Remote, TreeEntry,
+    ValidationResult, WorkingDiff, Worktree, WorktreeConflict, WorktreeRelation, WorktreeStatus,
 };
 use crate::watcher;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
 use tauri::{Emitter, Manager, WebviewWindowBuilder};
 use tauri::async_runtime::spawn_blocking;
+use tauri_plugin_dialog::DialogExt;
 
 #[tauri::command]
 pub async fn list_worktrees(repo_path: String) -> Result<Vec<Worktree>, String> {
@@ -17,9 +31,40 @@ pub async fn list_worktrees(repo_path: String) -> Result<Vec<Worktree>, String>
         .map_err(|e| e.to_string())?
 }
 
+/// JSON snapshot of a repository's worktrees, remotes and default branch
+#[tauri::command]
+pub async fn export_worktrees(repo_path: String) -> Result<String, String> {
+    spawn_blocking(move || git::export_worktrees(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Recreate the missing worktrees of a layout from export_worktrees
+#[tauri::command]
+pub async fn import_worktrees(
+    repo_path: String,
+    layout_json: String,
+) -> Result<Vec<CreateOutcome>, String> {
+    spawn_blocking(move || git::import_worktrees(&repo_path, &layout_json))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
-pub fn start_watching(app: tauri::AppHandle, paths: Vec<String>) -> Result<(), String> {
-    watcher::start_watching(app, paths)
+pub async fn get_worktree(repo_path: String, worktree_path: String) -> Result<Worktree, String> {
+    spawn_blocking(move || git::get_worktree(&repo_path, &worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Watch a window's worktrees, replacing what that window watched before
+#[tauri::command]
+pub fn start_watching(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    watcher::start_watching(app, window.label().to_string(), paths)
 }
 
 #[tauri::command]
@@ -33,114 +78,889 @@ pub async fn get_commit_history(
         .map_err(|e| e.to_string())?
 }
 
-#[tauri::command]
-pub async fn get_commit_diff(
-    worktree_path: String,
-    commit_sha: String,
-) -> Result<CommitDiff, String> {
-    spawn_blocking(move || git::get_commit_diff(&worktree_path, &commit_sha))
-        .await
-        .map_err(|e| e.to_string())?
+#[tauri::command]
+pub async fn get_log_summary(worktree_path: String, count: usize) -> Result<String, String> {
+    spawn_blocking(move || git::get_log_summary(&worktree_path, count))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_identity(worktree_path: String) -> Result<Identity, String> {
+    spawn_blocking(move || git::get_identity(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn set_worktree_identity(
+    worktree_path: String,
+    name: String,
+    email: String,
+    per_worktree: bool,
+) -> Result<Identity, String> {
+    spawn_blocking(move || git::set_worktree_identity(&worktree_path, &name, &email, per_worktree))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn stash_save(
+    worktree_path: String,
+    message: Option<String>,
+    keep_index: bool,
+    paths: Option<Vec<String>>,
+) -> Result<usize, String> {
+    spawn_blocking(move || {
+        git::stash_save(
+            &worktree_path,
+            message.as_deref(),
+            keep_index,
+            paths.as_deref(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_conflicts(worktree_path: String) -> Result<Vec<ConflictFile>, String> {
+    spawn_blocking(move || git::get_conflicts(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn mark_resolved(worktree_path: String, paths: Vec<String>) -> Result<(), String> {
+    spawn_blocking(move || git::mark_resolved(&worktree_path, &paths))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn resolve_conflict(
+    worktree_path: String,
+    file_path: String,
+    resolved_content: String,
+) -> Result<u32, String> {
+    spawn_blocking(move || git::resolve_conflict(&worktree_path, &file_path, resolved_content))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn continue_operation(worktree_path: String) -> Result<OperationState, String> {
+    spawn_blocking(move || git::continue_operation(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// The commit message template to pre-fill the commit message with, if any
+#[tauri::command]
+pub async fn get_commit_template(worktree_path: String) -> Result<Option<String>, String> {
+    spawn_blocking(move || {
+        let config = load_effective_config(&worktree_path)?;
+        git::get_commit_template(&worktree_path, config.commit_template.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn commit(
+    worktree_path: String,
+    message: String,
+    trailers: Option<Vec<String>>,
+) -> Result<CommitInfo, String> {
+    spawn_blocking(move || git::commit(&worktree_path, &message, &trailers.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn reword_head(
+    worktree_path: String,
+    new_message: String,
+    force: Option<bool>,
+) -> Result<CommitInfo, String> {
+    let force = force.unwrap_or(false);
+    spawn_blocking(move || git::reword_head(&worktree_path, &new_message, force))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn squash_head(
+    worktree_path: String,
+    count: u32,
+    message: String,
+    force: Option<bool>,
+) -> Result<CommitInfo, String> {
+    let force = force.unwrap_or(false);
+    spawn_blocking(move || git::squash_head(&worktree_path, count, &message, force))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_commit_diff(
+    worktree_path: String,
+    commit_sha: String,
+    ignore_whitespace: Option<bool>,
+) -> Result<CommitDiff, String> {
+    spawn_blocking(move || {
+        let config = config::load_config().unwrap_or_default();
+        git::get_commit_diff(
+            &worktree_path,
+            &commit_sha,
+            config.max_diff_bytes,
+            ignore_whitespace.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn branch_diff_from_base(
+    worktree_path: String,
+    base: Option<String>,
+) -> Result<CommitDiff, String> {
+    spawn_blocking(move || {
+        let config = config::load_config().unwrap_or_default();
+        git::branch_diff_from_base(&worktree_path, base.as_deref(), config.max_diff_bytes)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Commit counts and line changes per day since `since` (unix seconds)
+#[tauri::command]
+pub async fn commit_activity(
+    worktree_path: String,
+    since: i64,
+) -> Result<Vec<DayActivity>, String> {
+    spawn_blocking(move || git::commit_activity(&worktree_path, since))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn commits_ahead_of(worktree_path: String, base: Option<String>) -> Result<u32, String> {
+    spawn_blocking(move || git::commits_ahead_of(&worktree_path, base.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn needs_rebase(
+    worktree_path: String,
+    base: Option<String>,
+) -> Result<RebaseAdvice, String> {
+    spawn_blocking(move || git::needs_rebase(&worktree_path, base.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn worktree_relationships(repo_path: String) -> Result<Vec<WorktreeRelation>, String> {
+    spawn_blocking(move || git::worktree_relationships(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn lfs_status(worktree_path: String) -> Result<Vec<LfsFile>, String> {
+    spawn_blocking(move || git::lfs_status(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_file_diff(
+    worktree_path: String,
+    file_path: String,
+    commit_sha: Option<String>,
+    staged: Option<bool>,
+    ignore_whitespace: Option<bool>,
+) -> Result<Option<FileDiff>, String> {
+    spawn_blocking(move || {
+        git::get_file_diff(
+            &worktree_path,
+            &file_path,
+            commit_sha.as_deref(),
+            staged.unwrap_or(false),
+            ignore_whitespace.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_working_diff_for_file(
+    worktree_path: String,
+    file_path: String,
+    staged: bool,
+) -> Result<FileDiff, String> {
+    spawn_blocking(move || git::get_working_diff_for_file(&worktree_path, &file_path, staged))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_stash_diff(
+    repo_path: String,
+    stash_index: usize,
+    include_untracked: Option<bool>,
+) -> Result<CommitDiff, String> {
+    spawn_blocking(move || {
+        git::get_stash_diff(&repo_path, stash_index, include_untracked.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_file_at_commit(
+    worktree_path: String,
+    commit_sha: String,
+    file_path: String,
+) -> Result<FileBlob, String> {
+    spawn_blocking(move || git::get_file_at_commit(&worktree_path, &commit_sha, &file_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_tree(
+    worktree_path: String,
+    subpath: Option<String>,
+) -> Result<Vec<TreeEntry>, String> {
+    spawn_blocking(move || git::list_tree(&worktree_path, subpath.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn diff_summary(
+    worktree_path: String,
+    from: String,
+    to: Option<String>,
+) -> Result<DiffStats, String> {
+    spawn_blocking(move || git::diff_summary(&worktree_path, &from, to.as_deref()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn apply_patch(
+    worktree_path: String,
+    patch_text: String,
+    check_only: bool,
+) -> Result<ApplyResult, String> {
+    spawn_blocking(move || git::apply_patch(&worktree_path, patch_text, check_only))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn export_patch(worktree_path: String, staged_only: bool) -> Result<String, String> {
+    spawn_blocking(move || git::export_patch(&worktree_path, staged_only))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Export the worktree's changes to a file picked in a save dialog.
+/// Returns the saved path, or None if the dialog was cancelled.
+#[tauri::command]
+pub async fn save_patch(
+    app: tauri::AppHandle,
+    worktree_path: String,
+    staged_only: bool,
+) -> Result<Option<String>, String> {
+    spawn_blocking(move || {
+        let patch = git::export_patch(&worktree_path, staged_only)?;
+        if patch.is_empty() {
+            return Err("No changes to export".to_string());
+        }
+
+        let name = std::path::Path::new(&worktree_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "changes".to_string());
+        let Some(file) = app
+            .dialog()
+            .file()
+            .add_filter("Patch", &["patch", "diff"])
+            .set_file_name(format!("{}.patch", name))
+            .blocking_save_file()
+        else {
+            return Ok(None);
+        };
+
+        let path = file.into_path().map_err(|e| e.to_string())?;
+        std::fs::write(&path, patch).map_err(|e| format!("Failed to write patch: {}", e))?;
+        Ok(Some(path.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_working_diff(
+    worktree_path: String,
+    include_untracked: Option<bool>,
+    ignore_whitespace: Option<bool>,
+) -> Result<WorkingDiff, String> {
+    spawn_blocking(move || {
+        let config = config::load_config().unwrap_or_default();
+        git::get_working_diff(
+            &worktree_path,
+            include_untracked.unwrap_or(true),
+            config.max_diff_bytes,
+            ignore_whitespace.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn checkout_file(
+    worktree_path: String,
+    source_ref: String,
+    file_path: String,
+    force: Option<bool>,
+) -> Result<WorkingDiff, String> {
+    spawn_blocking(move || {
+        let config = config::load_config().unwrap_or_default();
+        git::checkout_file(
+            &worktree_path,
+            &source_ref,
+            &file_path,
+            force.unwrap_or(false),
+            config.max_diff_bytes,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_worktree_status(worktree_path: String) -> Result<WorktreeStatus, String> {
+    spawn_blocking(move || git::get_worktree_status_by_path(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn count_changes(worktree_path: String) -> Result<ChangeCounts, String> {
+    spawn_blocking(move || git::count_changes(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn is_worktree_clean(worktree_path: String) -> Result<CleanState, String> {
+    spawn_blocking(move || git::is_worktree_clean(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn add_watch_path(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    path: String,
+) -> Result<(), String> {
+    watcher::add_watch_path(app, window.label().to_string(), path)
+}
+
+#[tauri::command]
+pub fn start_watching_diff(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    worktree_path: String,
+) -> Result<(), String> {
+    watcher::start_watching_diff(app, window.label().to_string(), worktree_path)
+}
+
+#[tauri::command]
+pub fn stop_watching_diff(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    worktree_path: String,
+) -> Result<(), String> {
+    watcher::stop_watching_diff(app, window.label().to_string(), worktree_path)
+}
+
+#[tauri::command]
+pub fn remove_watch_path(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    path: String,
+) -> Result<(), String> {
+    watcher::remove_watch_path(app, window.label().to_string(), path)
+}
+
+/// The most recent error from this window's worktree or diff watchers, if any
+#[tauri::command]
+pub fn get_last_watch_error(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+) -> Option<watcher::WatchError> {
+    watcher::last_watch_error(&app, window.label())
+}
+
+#[tauri::command]
+pub async fn create_worktree(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    repo_path: String,
+    options: CreateWorktreeOptions,
+    watch: Option<bool>,
+) -> Result<Worktree, String> {
+    let worktree = spawn_blocking(move || {
+        let config = load_effective_config(&repo_path)?;
+        let base_dir = config
+            .worktree_base_dir
+            .map(|dir| config::expand_tilde(&dir));
+        git::create_worktree(&repo_path, options, base_dir.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // Register the new worktree with the watcher so it live-updates immediately
+    let path = worktree.path.to_string_lossy().to_string();
+    let label = window.label().to_string();
+    if watch.unwrap_or(false) {
+        if let Err(e) = watcher::add_watch_path(app.clone(), label.clone(), path.clone()) {
+            eprintln!("Failed to watch new worktree: {}", e);
+        }
+    }
+
+    // Run the configured actions in the background, as scripts may take a
+    // while, and report how they went with an event to the creating window
+    tauri::async_runtime::spawn(async move {
+        let outcomes = spawn_blocking({
+            let path = path.clone();
+            move || run_on_create_actions(&path)
+        })
+        .await;
+        match outcomes.map_err(|e| e.to_string()).and_then(|r| r) {
+            Ok(outcomes) if !outcomes.is_empty() => {
+                let _ = app.emit_to(
+                    label.as_str(),
+                    "on-create-actions-finished",
+                    OnCreateActionsFinished {
+                        worktree_path: path,
+                        outcomes,
+                    },
+                );
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to run on-create actions: {}", e),
+        }
+    });
+
+    Ok(worktree)
+}
+
+/// Run the on_create_actions configured for a new worktree, continuing past
+/// failures
+fn run_on_create_actions(worktree_path: &str) -> Result<Vec<ActionOutcome>, String> {
+    let config = load_effective_config(worktree_path)?;
+    Ok(config
+        .on_create_actions
+        .iter()
+        .map(|action_ref| {
+            let result = config
+                .actions
+                .iter()
+                .find(|a| a.id == action_ref.id)
+                .ok_or_else(|| format!("Unknown action: {}", action_ref.id))
+                .and_then(|action| execute_action(action, &config, worktree_path));
+            match result {
+                Ok(result) => ActionOutcome {
+                    action_id: action_ref.id.clone(),
+                    result,
+                    error: None,
+                },
+                Err(e) => ActionOutcome {
+                    action_id: action_ref.id.clone(),
+                    result: None,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub async fn validate_worktree_target(
+    repo_path: String,
+    path: String,
+) -> Result<ValidationResult, String> {
+    spawn_blocking(move || git::validate_worktree_target(&repo_path, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn delete_worktree(
+    app: tauri::AppHandle,
+    repo_path: String,
+    worktree_path: String,
+    force: bool,
+    delete_branch: bool,
+) -> Result<DeleteWorktreeResult, String> {
+    let path = worktree_path.clone();
+    let result =
+        spawn_blocking(move || git::delete_worktree(&repo_path, &path, force, delete_branch))
+            .await
+            .map_err(|e| e.to_string())??;
+
+    // Drop the watch so the debouncer doesn't hold on to a missing directory
+    if let Err(e) = watcher::remove_watch_path_everywhere(app, worktree_path) {
+        eprintln!("Failed to unwatch deleted worktree: {}", e);
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn delete_worktrees(
+    app: tauri::AppHandle,
+    repo_path: String,
+    worktree_paths: Vec<String>,
+    force: bool,
+    delete_branch: Option<bool>,
+) -> Result<Vec<DeleteOutcome>, String> {
+    let outcomes = spawn_blocking(move || {
+        git::delete_worktrees(
+            &repo_path,
+            &worktree_paths,
+            force,
+            delete_branch.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for outcome in outcomes.iter().filter(|o| o.error.is_none()) {
+        if let Err(e) = watcher::remove_watch_path_everywhere(app.clone(), outcome.path.clone()) {
+            eprintln!("Failed to unwatch deleted worktree: {}", e);
+        }
+    }
+
+    Ok(outcomes)
+}
+
+#[tauri::command]
+pub async fn worktree_conflicts(repo_path: String) -> Result<Vec<WorktreeConflict>, String> {
+    spawn_blocking(move || git::worktree_conflicts(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn prune_worktrees(repo_path: String) -> Result<PruneResult, String> {
+    spawn_blocking(move || git::prune_worktrees(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_branches(repo_path: String) -> Result<Vec<BranchInfo>, String> {
+    spawn_blocking(move || git::list_branches(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn rename_current_branch(
+    worktree_path: String,
+    new_name: String,
+    set_upstream: Option<bool>,
+) -> Result<BranchInfo, String> {
+    spawn_blocking(move || {
+        git::rename_current_branch(&worktree_path, &new_name, set_upstream.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn branches_containing(
+    repo_path: String,
+    sha: String,
+    include_remotes: Option<bool>,
+) -> Result<BranchesContaining, String> {
+    spawn_blocking(move || {
+        git::branches_containing(&repo_path, &sha, include_remotes.unwrap_or(true))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn branch_exists(
+    repo_path: String,
+    name: String,
+    query_remotes: bool,
+) -> Result<BranchExistence, String> {
+    spawn_blocking(move || git::branch_exists(&repo_path, &name, query_remotes))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn recent_branches(repo_path: String, limit: usize) -> Result<Vec<String>, String> {
+    spawn_blocking(move || git::recent_branches(&repo_path, limit))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn default_branch(
+    repo_path: String,
+    remote: Option<String>,
+    query_remote: Option<bool>,
+) -> Result<Option<String>, String> {
+    spawn_blocking(move || {
+        git::default_branch(
+            &repo_path,
+            remote.as_deref().unwrap_or("origin"),
+            query_remote.unwrap_or(false),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn pr_url(worktree_path: String) -> Result<Option<String>, String> {
+    spawn_blocking(move || git::pr_url(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stable identifier for a repository, the key for per-repository settings
+#[tauri::command]
+pub async fn repo_fingerprint(repo_path: String) -> Result<String, String> {
+    spawn_blocking(move || git::repo_fingerprint(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn list_remotes(repo_path: String) -> Result<Vec<Remote>, String> {
+    spawn_blocking(move || git::list_remotes(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn prune_remote(
+    repo_path: String,
+    remote: String,
+    fetch: Option<bool>,
+) -> Result<Vec<String>, String> {
+    spawn_blocking(move || git::prune_remote(&repo_path, &remote, fetch.unwrap_or(false)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn gitdir_for_worktree(worktree_path: String) -> Result<GitDirs, String> {
+    spawn_blocking(move || git::gitdir_for_worktree(&worktree_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn object_store_size(repo_path: String) -> Result<u64, String> {
+    spawn_blocking(move || git::object_store_size(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Run `git gc`, emitting its progress as `git-progress` events
+#[tauri::command]
+pub async fn run_gc(
+    app: tauri::AppHandle,
+    repo_path: String,
+    aggressive: bool,
+) -> Result<GcResult, String> {
+    spawn_blocking(move || {
+        git::run_gc(&repo_path, aggressive, |message| {
+            let _ = app.emit(
+                "git-progress",
+                GitProgress {
+                    repo_path: repo_path.clone(),
+                    message: message.to_string(),
+                },
+            );
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn discover_repos(root: String, max_depth: usize) -> Result<Vec<String>, String> {
+    spawn_blocking(move || git::discover_repos(&config::expand_tilde(&root), max_depth))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn open_in_terminal(
+    path: String,
+    terminal: Option<String>,
+    new_window: Option<bool>,
+) -> Result<(), String> {
+    let config = load_effective_config(&path)?;
+    let terminal = config.terminal_or(terminal);
+    terminal::open_terminal(
+        &path,
+        &terminal,
+        &config.terminal_commands,
+        new_window.unwrap_or(true),
+    )
+}
+
+/// Launch a terminal configuration against a throwaway directory. Only reports
+/// whether the process could be spawned, not whether the window appeared.
+#[tauri::command]
+pub async fn test_terminal(terminal: String) -> Result<(), String> {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let config = config::load_config()?;
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let test_dir = std::env::temp_dir().join(format!("woodeye-terminal-test-{}", nanos));
+    std::fs::create_dir_all(&test_dir)
+        .map_err(|e| format!("Failed to create test directory: {}", e))?;
+
+    let result = terminal::open_terminal(
+        &test_dir.to_string_lossy(),
+        &terminal,
+        &config.terminal_commands,
+        true,
+    );
+
+    // The terminal starts asynchronously, so give it time to cd into the
+    // directory before removing it
+    let cleanup_delay = if result.is_ok() {
+        Duration::from_secs(10)
+    } else {
+        Duration::ZERO
+    };
+    std::thread::spawn(move || {
+        std::thread::sleep(cleanup_delay);
+        let _ = std::fs::remove_dir_all(&test_dir);
+    });
+
+    result
 }
 
 #[tauri::command]
-pub async fn get_working_diff(worktree_path: String) -> Result<WorkingDiff, String> {
-    spawn_blocking(move || git::get_working_diff(&worktree_path))
-        .await
-        .map_err(|e| e.to_string())?
+pub async fn open_claude_in_terminal(path: String, terminal: Option<String>) -> Result<(), String> {
+    terminal::open_claude(&path, terminal.as_deref().unwrap_or("terminal"))
 }
 
 #[tauri::command]
-pub async fn get_worktree_status(worktree_path: String) -> Result<WorktreeStatus, String> {
-    spawn_blocking(move || git::get_worktree_status_by_path(&worktree_path))
-        .await
-        .map_err(|e| e.to_string())?
+pub fn set_theme_menu_state(app_handle: tauri::AppHandle, theme: String) -> Result<(), String> {
+    menu::update_theme_checkmarks(&app_handle, &theme)
 }
 
 #[tauri::command]
-pub async fn create_worktree(
-    repo_path: String,
-    options: CreateWorktreeOptions,
-) -> Result<Worktree, String> {
-    spawn_blocking(move || git::create_worktree(&repo_path, options))
+pub async fn list_claude_sessions() -> Result<Vec<ClaudeSession>, String> {
+    spawn_blocking(claude_status::list_sessions)
         .await
         .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn delete_worktree(
-    repo_path: String,
+pub async fn get_worktree_claude_sessions(
     worktree_path: String,
-    force: bool,
-) -> Result<(), String> {
-    spawn_blocking(move || git::delete_worktree(&repo_path, &worktree_path, force))
-        .await
-        .map_err(|e| e.to_string())?
+    jsonl_only: Option<bool>,
+) -> Result<Vec<ClaudeSession>, String> {
+    spawn_blocking(move || {
+        claude_status::list_sessions_for_worktree(&worktree_path, jsonl_only.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
+/// Classify the files in the status dir and report which are read, and how
 #[tauri::command]
-pub async fn prune_worktrees(repo_path: String) -> Result<PruneResult, String> {
-    spawn_blocking(move || git::prune_worktrees(&repo_path))
+pub async fn diagnose_status_files() -> Result<StatusDiagnosis, String> {
+    spawn_blocking(claude_status::diagnose_status_files)
         .await
         .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn list_branches(repo_path: String) -> Result<Vec<BranchInfo>, String> {
-    spawn_blocking(move || git::list_branches(&repo_path))
+pub async fn list_claude_projects() -> Result<Vec<ClaudeProject>, String> {
+    spawn_blocking(claude_status::list_claude_projects)
         .await
         .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn open_in_terminal(path: String, terminal: String) -> Result<(), String> {
-    use std::process::Command;
-
-    let result = match terminal.as_str() {
-        "terminal" => Command::new("open").args(["-a", "Terminal", &path]).spawn(),
-        "warp" => Command::new("open")
-            .arg(format!("warp://action/new_window?path={}", path))
-            .spawn(),
-        "iterm" => Command::new("open").args(["-a", "iTerm", &path]).spawn(),
-        "ghostty" => Command::new("open").args(["-a", "ghostty", &path]).spawn(),
-        _ => return Err(format!("Unknown terminal: {}", terminal)),
-    };
-
-    result.map_err(|e| format!("Failed to open terminal: {}", e))?;
-    Ok(())
+pub async fn get_all_claude_statuses(
+    worktree_paths: Vec<String>,
+) -> Result<HashMap<String, Vec<ClaudeSession>>, String> {
+    spawn_blocking(move || claude_status::get_all_statuses(&worktree_paths))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
-#[tauri::command]
-pub async fn open_claude_in_terminal(path: String) -> Result<(), String> {
-    use std::process::Command;
+/// Open a file or URL with the platform's default application
+fn open_with_default_app(target: impl AsRef<std::ffi::OsStr>) -> Result<(), String> {
+    let target = target.as_ref();
 
-    // Use AppleScript to open Terminal and run claude
-    let script = format!(
-        r#"tell application "Terminal"
-            do script "cd '{}' && claude"
-            activate
-        end tell"#,
-        path.replace("'", "'\\''") // Escape single quotes
-    );
+    #[cfg(target_os = "macos")]
+    let mut command = shell_env::command("open");
+    #[cfg(target_os = "windows")]
+    let mut command = shell_env::command("explorer");
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = shell_env::command("xdg-open");
 
-    Command::new("osascript")
-        .args(["-e", &script])
+    command
+        .arg(target)
         .spawn()
-        .map_err(|e| format!("Failed to open terminal: {}", e))?;
+        .map_err(|e| format!("Failed to open {}: {}", target.to_string_lossy(), e))?;
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn set_theme_menu_state(app_handle: tauri::AppHandle, theme: String) -> Result<(), String> {
-    menu::update_theme_checkmarks(&app_handle, &theme)
+pub async fn open_session_log(session_id: String) -> Result<(), String> {
+    spawn_blocking(move || {
+        let log_path = claude_status::find_session_log(&session_id)?;
+        open_with_default_app(&log_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-pub async fn list_claude_sessions() -> Result<Vec<ClaudeSession>, String> {
-    spawn_blocking(claude_status::list_sessions)
-        .await
-        .map_err(|e| e.to_string())?
+pub async fn reveal_status_dir() -> Result<(), String> {
+    spawn_blocking(|| {
+        let status_dir = claude_status::get_status_dir()?;
+        std::fs::create_dir_all(&status_dir)
+            .map_err(|e| format!("Failed to create status directory: {}", e))?;
+        open_with_default_app(&status_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -150,14 +970,42 @@ pub async fn delete_claude_session(session_id: String) -> Result<(), String> {
         .map_err(|e| e.to_string())?
 }
 
+#[tauri::command]
+pub async fn set_session_state(
+    app: tauri::AppHandle,
+    session_id: String,
+    state: String,
+) -> Result<(), String> {
+    spawn_blocking(move || claude_status::set_session_state(&session_id, &state))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    // The status watcher would pick this up too, but don't depend on it running
+    let _ = app.emit("claude-status-changed", ());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_session_name(
+    app: tauri::AppHandle,
+    session_id: String,
+    name: String,
+) -> Result<(), String> {
+    spawn_blocking(move || claude_status::set_session_name(&session_id, &name))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    let _ = app.emit("claude-status-changed", ());
+    Ok(())
+}
+
 #[tauri::command]
 pub fn start_watching_claude_status(app: tauri::AppHandle) -> Result<(), String> {
     use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
     use std::sync::mpsc;
     use std::time::Duration;
 
-    let status_dir = claude_status::get_status_dir()
-        .ok_or("Could not determine status directory")?;
+    let status_dir = claude_status::get_status_dir()?;
 
     // Create the directory if it doesn't exist
     if !status_dir.exists() {
@@ -176,24 +1024,53 @@ pub fn start_watching_claude_status(app: tauri::AppHandle) -> Result<(), String>
         .map_err(|e| format!("Failed to watch status directory: {}", e))?;
 
     // Store the debouncer in app state to keep it alive
-    app.manage(ClaudeStatusWatcherState { _debouncer: debouncer });
+    let health = Arc::new(WatcherHealthState {
+        running: AtomicBool::new(true),
+        started_at: unix_now(),
+        last_event_at: AtomicU64::new(0),
+    });
+    app.manage(ClaudeStatusWatcherState {
+        _debouncer: debouncer,
+        health: health.clone(),
+    });
+
+    let history_enabled = config::load_config().unwrap_or_default().history_enabled;
 
     // Spawn thread to handle events
     let app_handle = app.clone();
     std::thread::spawn(move || {
+        // Track session transitions for long-term history, if enabled
+        let mut tracker = if history_enabled {
+            let initial = claude_status::list_sessions().unwrap_or_default();
+            history::SessionTracker::new(&initial)
+                .map_err(|e| eprintln!("Session history disabled: {}", e))
+                .ok()
+        } else {
+            None
+        };
+
         while let Ok(result) = rx.recv() {
             match result {
                 Ok(events) => {
+                    health.last_event_at.store(unix_now(), Ordering::Relaxed);
                     let has_changes = events
                         .iter()
                         .any(|e| matches!(e.kind, DebouncedEventKind::Any));
                     if has_changes {
+                        if let Some(ref mut tracker) = tracker {
+                            if let Ok(sessions) = claude_status::list_sessions() {
+                                if let Err(e) = tracker.record(&sessions) {
+                                    eprintln!("Failed to record session history: {}", e);
+                                }
+                            }
+                        }
                         let _ = app_handle.emit("claude-status-changed", ());
                     }
                 }
                 Err(e) => eprintln!("Claude status watch error: {:?}", e),
             }
         }
+        health.running.store(false, Ordering::Relaxed);
     });
 
     Ok(())
@@ -201,43 +1078,263 @@ pub fn start_watching_claude_status(app: tauri::AppHandle) -> Result<(), String>
 
 struct ClaudeStatusWatcherState {
     _debouncer: notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>,
+    health: Arc<WatcherHealthState>,
+}
+
+/// Liveness of the Claude status watcher thread, shared with it
+struct WatcherHealthState {
+    running: AtomicBool,
+    started_at: u64,
+    /// Unix seconds of the last debounced batch of events, 0 if none yet
+    last_event_at: AtomicU64,
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(serde::Serialize)]
+pub struct WatcherHealth {
+    /// Whether the watcher was started and its event thread is still alive
+    pub running: bool,
+    pub started_at: Option<u64>,
+    /// When the OS last delivered events for the status directory. If this
+    /// lags behind known session activity, events aren't arriving.
+    pub last_event_at: Option<u64>,
+}
+
+#[tauri::command]
+pub fn get_watcher_health(app: tauri::AppHandle) -> WatcherHealth {
+    let Some(state) = app.try_state::<ClaudeStatusWatcherState>() else {
+        return WatcherHealth {
+            running: false,
+            started_at: None,
+            last_event_at: None,
+        };
+    };
+    let last_event_at = state.health.last_event_at.load(Ordering::Relaxed);
+    WatcherHealth {
+        running: state.health.running.load(Ordering::Relaxed),
+        started_at: Some(state.health.started_at),
+        last_event_at: (last_event_at > 0).then_some(last_event_at),
+    }
 }
 
+/// Open (or show) the Claude status window. Pass `focus: false` to present it
+/// as a passive monitor without taking focus from the app in use.
 #[tauri::command]
-pub async fn open_claude_status_window(app: tauri::AppHandle) -> Result<(), String> {
+pub async fn open_claude_status_window(
+    app: tauri::AppHandle,
+    focus: Option<bool>,
+) -> Result<(), String> {
+    let focus = focus.unwrap_or(true);
+
     // Check if window already exists
     if let Some(window) = app.get_webview_window("claude-status") {
         window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
+        if focus {
+            window.set_focus().map_err(|e| e.to_string())?;
+        }
         return Ok(());
     }
 
     // Create new window
     let url = tauri::WebviewUrl::App("claude-status.html".into());
+    let config = config::load_config().unwrap_or_default();
 
     WebviewWindowBuilder::new(&app, "claude-status", url)
         .title("Claude Sessions")
         .inner_size(400.0, 600.0)
         .resizable(true)
+        .always_on_top(config.claude_status_always_on_top)
+        .visible_on_all_workspaces(config.claude_status_level == WindowLevel::AllWorkspaces)
+        // Lets the page's opacity show what's behind the window
+        .transparent(true)
+        .focused(focus)
         .build()
         .map_err(|e| format!("Failed to create window: {}", e))?;
 
     Ok(())
 }
 
+/// Window label for a repository window. Derived from the canonical path so
+/// reopening the same repository finds the existing window.
+fn repo_window_label(repo_path: &str) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let path = std::path::Path::new(repo_path);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("repo-{:016x}", hasher.finish())
+}
+
+/// Open another main window with the given repository selected, or focus the
+/// one already showing it
+#[tauri::command]
+pub async fn open_repo_window(app: tauri::AppHandle, repo_path: String) -> Result<(), String> {
+    let label = repo_window_label(&repo_path);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return Ok(());
+    }
+
+    let name = std::path::Path::new(&repo_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo_path.clone());
+    // The frontend picks this up instead of the last opened repository
+    let init_script = format!(
+        "window.__WOODEYE_REPO__ = {};",
+        serde_json::to_string(&repo_path).map_err(|e| e.to_string())?
+    );
+
+    WebviewWindowBuilder::new(&app, &label, tauri::WebviewUrl::App("index.html".into()))
+        .title(format!("Woodeye - {}", name))
+        .inner_size(1200.0, 800.0)
+        .resizable(true)
+        .initialization_script(&init_script)
+        .build()
+        .map_err(|e| format!("Failed to create window: {}", e))?;
+
+    Ok(())
+}
+
+/// Claude hooks were found missing on startup and re-applied
+#[derive(serde::Serialize, Clone)]
+pub struct HooksAutoApplied {
+    /// Set when re-applying them failed
+    pub error: Option<String>,
+}
+
+/// The startup hooks outcome, until a window has reported it
+pub struct HooksAutoApplyState(pub std::sync::Mutex<Option<HooksAutoApplied>>);
+
+/// Take the outcome of re-applying Claude hooks on startup. It's returned
+/// once, so only the first window to ask reports it.
+#[tauri::command]
+pub fn take_hooks_auto_applied(app: tauri::AppHandle) -> Option<HooksAutoApplied> {
+    let state = app.try_state::<HooksAutoApplyState>()?;
+    let mut outcome = state.0.lock().ok()?;
+    outcome.take()
+}
+
 #[tauri::command]
 pub async fn set_claude_status_always_on_top(
     app: tauri::AppHandle,
     always_on_top: bool,
 ) -> Result<(), String> {
+    // Remember the choice for the next time the window is opened
+    let level = spawn_blocking(move || {
+        let mut config = config::load_config()?;
+        config.claude_status_always_on_top = always_on_top;
+        config.claude_status_level = match (always_on_top, config.claude_status_level) {
+            (false, _) => WindowLevel::Normal,
+            (true, WindowLevel::Normal) => WindowLevel::Floating,
+            (true, level) => level,
+        };
+        config::save_config(&config)?;
+        Ok::<_, String>(config.claude_status_level)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
     if let Some(window) = app.get_webview_window("claude-status") {
         window
             .set_always_on_top(always_on_top)
             .map_err(|e| format!("Failed to set always on top: {}", e))?;
+        window
+            .set_visible_on_all_workspaces(level == WindowLevel::AllWorkspaces)
+            .map_err(|e| format!("Failed to set workspace visibility: {}", e))?;
     }
     Ok(())
 }
 
+/// Set the Claude status window's opacity and stacking level, e.g. to float
+/// it as a translucent HUD. The window is transparent and its frontend applies
+/// the opacity to the page. It's clamped to MIN_WINDOW_OPACITY so the window
+/// can't become invisible.
+#[tauri::command]
+pub async fn set_claude_status_window_style(
+    app: tauri::AppHandle,
+    opacity: f64,
+    level: WindowLevel,
+) -> Result<ClaudeStatusSettings, String> {
+    let settings = ClaudeStatusSettings {
+        always_on_top: level != WindowLevel::Normal,
+        level,
+        opacity: config::clamp_opacity(opacity),
+    };
+
+    if let Some(window) = app.get_webview_window("claude-status") {
+        window
+            .set_always_on_top(settings.always_on_top)
+            .map_err(|e| format!("Failed to set always on top: {}", e))?;
+        window
+            .set_visible_on_all_workspaces(level == WindowLevel::AllWorkspaces)
+            .map_err(|e| format!("Failed to set workspace visibility: {}", e))?;
+        let _ = window.emit("claude-status-style-changed", settings.clone());
+    }
+
+    let saved = settings.clone();
+    spawn_blocking(move || {
+        let mut config = config::load_config()?;
+        config.claude_status_always_on_top = saved.always_on_top;
+        config.claude_status_level = saved.level;
+        config.claude_status_opacity = saved.opacity;
+        config::save_config(&config)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    Ok(settings)
+}
+
+#[derive(serde::Serialize, Clone)]
+pub struct ClaudeStatusSettings {
+    pub always_on_top: bool,
+    pub level: WindowLevel,
+    pub opacity: f64,
+}
+
+#[tauri::command]
+pub async fn get_claude_status_settings() -> Result<ClaudeStatusSettings, String> {
+    spawn_blocking(|| {
+        let config = config::load_config()?;
+        Ok(ClaudeStatusSettings {
+            always_on_top: config.claude_status_always_on_top,
+            level: config.claude_status_level,
+            opacity: config::clamp_opacity(config.claude_status_opacity),
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn get_session_history(since: i64, until: i64) -> Result<Vec<SessionHistoryDay>, String> {
+    spawn_blocking(move || {
+        let config = config::load_config()?;
+        if !config.history_enabled {
+            return Err("Session history is disabled".to_string());
+        }
+        history::get_session_history(since, until)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn check_claude_cli() -> Result<ClaudeCliInfo, String> {
+    spawn_blocking(claude_status::check_claude_cli)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_claude_hooks_state() -> Result<HooksState, String> {
     spawn_blocking(claude_status::get_hooks_state)
@@ -253,105 +1350,312 @@ pub async fn remove_claude_hooks() -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn apply_claude_hooks() -> Result<(), String> {
-    spawn_blocking(claude_status::apply_hooks)
+pub async fn apply_claude_hooks() -> Result<ApplyHooksResult, String> {
+    spawn_blocking(|| {
+        let config = config::load_config()?;
+        claude_status::apply_hooks(config.supported_hook_events.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn focus_terminal_for_path(path: String) -> Result<bool, String> {
+    spawn_blocking(move || terminal::focus_terminal_for_path(&path))
         .await
         .map_err(|e| e.to_string())?
 }
 
+#[derive(serde::Serialize)]
+pub struct WorktreeRow {
+    pub worktree: Worktree,
+    pub status: Option<WorktreeStatus>,
+    pub claude: Option<WorktreeClaudeStatus>,
+    /// Set when the status or Claude sessions couldn't be loaded for this row
+    pub error: Option<String>,
+}
+
+/// Load worktrees together with their status and Claude sessions in one call.
+/// Statuses are fetched in parallel; a failure for one worktree is reported on
+/// its row instead of failing the whole dashboard.
 #[tauri::command]
-pub async fn focus_terminal_for_path(path: String) -> Result<bool, String> {
-    use std::process::Command;
+pub async fn get_worktrees_dashboard(repo_path: String) -> Result<Vec<WorktreeRow>, String> {
+    spawn_blocking(move || {
+        let worktrees = git::get_all_worktrees(&repo_path)?;
+
+        let paths: Vec<String> = worktrees
+            .iter()
+            .map(|w| w.path.to_string_lossy().to_string())
+            .collect();
+        let sessions = claude_status::get_all_statuses(&paths);
+
+        let rows = worktrees
+            .into_par_iter()
+            .zip(paths)
+            .map(|(worktree, path)| {
+                let mut errors = Vec::new();
+
+                let status = git::get_worktree_status_by_path(&path)
+                    .map_err(|e| errors.push(e))
+                    .ok();
+
+                let claude = match &sessions {
+                    Ok(grouped) => Some(WorktreeClaudeStatus::new(
+                        grouped.get(&path).cloned().unwrap_or_default(),
+                    )),
+                    Err(e) => {
+                        errors.push(e.clone());
+                        None
+                    }
+                };
 
-    // Step 1: Find processes with cwd matching the target path using lsof
-    let lsof_output = Command::new("lsof")
-        .args(["-d", "cwd"])
-        .output()
-        .map_err(|e| format!("Failed to run lsof: {}", e))?;
-
-    let lsof_str = String::from_utf8_lossy(&lsof_output.stdout);
-
-    // Step 2: Parse lsof output to find PIDs with matching cwd
-    // Format: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
-    let matching_pids: Vec<String> = lsof_str
-        .lines()
-        .filter(|line| line.ends_with(&path) || line.contains(&format!("{} ", path)))
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                Some(parts[1].to_string())
-            } else {
-                None
-            }
-        })
-        .collect();
+                WorktreeRow {
+                    worktree,
+                    status,
+                    claude,
+                    error: (!errors.is_empty()).then(|| errors.join("; ")),
+                }
+            })
+            .collect();
+
+        Ok(rows)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    // Step 3: For each PID, get its tty
-    for pid in matching_pids {
-        let ps_output = Command::new("ps")
-            .args(["-p", &pid, "-o", "tty="])
-            .output()
-            .map_err(|e| format!("Failed to run ps: {}", e))?;
+#[derive(serde::Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AttentionSeverity {
+    Info,
+    Warning,
+    Critical,
+}
 
-        let tty = String::from_utf8_lossy(&ps_output.stdout).trim().to_string();
+#[derive(serde::Serialize, Clone, Copy, PartialEq)]
+pub enum AttentionKind {
+    /// Unmerged files, usually left by a rebase or merge
+    Conflicts,
+    /// A Claude session is waiting for input or approval
+    ClaudeWaiting,
+    /// Commits that haven't been pushed to the upstream branch
+    Unpushed,
+    /// The worktree's state couldn't be checked
+    Error,
+}
 
-        // Skip if no tty (e.g., "??") or empty
-        if tty.is_empty() || tty == "??" {
-            continue;
-        }
+/// Something in a worktree that needs the user's attention
+#[derive(serde::Serialize, Clone)]
+pub struct AttentionItem {
+    pub kind: AttentionKind,
+    pub severity: AttentionSeverity,
+    pub worktree_path: String,
+    pub message: String,
+    /// Id of the action that deals with it, e.g. "push"
+    pub action: Option<String>,
+    /// The Claude session, for ClaudeWaiting
+    pub session_id: Option<String>,
+}
 
-        // Step 4: Try to focus Terminal tab with this tty
-        let tty_path = format!("/dev/{}", tty);
-        let script = format!(
-            r#"tell application "System Events"
-    if not (exists process "Terminal") then
-        return false
-    end if
-end tell
-
-tell application "Terminal"
-    repeat with w in windows
-        repeat with t in tabs of w
-            if tty of t is "{tty_path}" then
-                set frontmost of w to true
-                set selected of t to true
-                activate
-                return true
-            end if
-        end repeat
-    end repeat
-    return false
-end tell"#,
-            tty_path = tty_path.replace("\"", "\\\"")
+/// Collect everything needing attention in the given worktrees (all of the
+/// repository's when empty), most severe first. Worktrees are checked in
+/// parallel; one that can't be checked is reported as an Error item.
+#[tauri::command]
+pub async fn get_attention_items(
+    repo_path: String,
+    worktree_paths: Vec<String>,
+) -> Result<Vec<AttentionItem>, String> {
+    spawn_blocking(move || {
+        let paths = if worktree_paths.is_empty() {
+            git::get_all_worktrees(&repo_path)?
+                .into_iter()
+                .filter(|w| w.exists_on_disk && !w.is_bare)
+                .map(|w| w.path.to_string_lossy().to_string())
+                .collect()
+        } else {
+            worktree_paths
+        };
+
+        let (sessions, mut items) = rayon::join(
+            || claude_status::get_all_statuses(&paths),
+            || {
+                paths
+                    .par_iter()
+                    .flat_map_iter(|path| worktree_attention_items(path))
+                    .collect::<Vec<_>>()
+            },
         );
 
-        let output = Command::new("osascript")
-            .args(["-e", &script])
-            .output()
-            .map_err(|e| format!("Failed to run AppleScript: {}", e))?;
+        let item = |kind, severity, path: &str, message: String| AttentionItem {
+            kind,
+            severity,
+            worktree_path: path.to_string(),
+            message,
+            action: None,
+            session_id: None,
+        };
+        match sessions {
+            Ok(grouped) => {
+                for (path, sessions) in grouped {
+                    for session in sessions.iter().filter(|s| s.is_waiting() && !s.stale) {
+                        let name = session.name.as_deref().unwrap_or("Claude session");
+                        items.push(AttentionItem {
+                            action: Some("focus_session".to_string()),
+                            session_id: Some(session.session_id.clone()),
+                            ..item(
+                                AttentionKind::ClaudeWaiting,
+                                AttentionSeverity::Warning,
+                                &path,
+                                format!("{} is waiting for input", name),
+                            )
+                        });
+                    }
+                }
+            }
+            Err(e) => items.push(item(
+                AttentionKind::Error,
+                AttentionSeverity::Info,
+                &repo_path,
+                format!("Could not read Claude sessions: {}", e),
+            )),
+        }
+
+        items.sort_by_key(|i| std::cmp::Reverse(i.severity));
+        Ok(items)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Conflicts and unpushed commits in one worktree
+fn worktree_attention_items(path: &str) -> Vec<AttentionItem> {
+    let item = |kind, severity, message: String, action: Option<&str>| AttentionItem {
+        kind,
+        severity,
+        worktree_path: path.to_string(),
+        message,
+        action: action.map(str::to_string),
+        session_id: None,
+    };
 
-        let result = String::from_utf8_lossy(&output.stdout).trim().to_lowercase();
-        if result == "true" {
-            return Ok(true);
+    let status = match git::get_worktree_status_by_path(path) {
+        Ok(status) => status,
+        Err(e) => {
+            return vec![item(
+                AttentionKind::Error,
+                AttentionSeverity::Info,
+                format!("Could not check status: {}", e),
+                None,
+            )]
         }
+    };
+
+    let mut items = Vec::new();
+    if status.conflicted > 0 {
+        items.push(item(
+            AttentionKind::Conflicts,
+            AttentionSeverity::Critical,
+            format!("{} conflicted file(s)", status.conflicted),
+            Some("resolve_conflicts"),
+        ));
     }
+    if let Some(upstream) = git::get_upstream_info(path).filter(|u| u.ahead > 0) {
+        items.push(item(
+            AttentionKind::Unpushed,
+            AttentionSeverity::Info,
+            format!(
+                "{} commit(s) not pushed to {}",
+                upstream.ahead, upstream.remote_branch
+            ),
+            Some("push"),
+        ));
+    }
+    items
+}
 
-    Ok(false)
+#[derive(serde::Serialize)]
+pub struct AppVersion {
+    pub version: String,
+    pub commit: Option<String>,
+    pub build_date: Option<String>,
+}
+
+#[tauri::command]
+pub fn get_app_version() -> AppVersion {
+    AppVersion {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        commit: option_env!("WOODEYE_GIT_COMMIT").map(String::from),
+        build_date: option_env!("WOODEYE_BUILD_DATE").map(String::from),
+    }
 }
 
 #[derive(serde::Serialize)]
+pub struct FocusResult {
+    pub session_id: String,
+    pub project_path: String,
+    pub focused: bool,
+    pub error: Option<String>,
+}
+
+/// Bring forward the terminal of every Claude session waiting for input.
+/// Sessions are handled one at a time so the AppleScript calls don't race.
+#[tauri::command]
+pub async fn focus_all_waiting_terminals() -> Result<Vec<FocusResult>, String> {
+    spawn_blocking(|| {
+        let sessions = claude_status::list_sessions()?;
+
+        Ok(sessions
+            .into_iter()
+            .filter(|session| session.is_waiting())
+            .map(|session| {
+                let (focused, error) =
+                    match terminal::focus_terminal_for_path(&session.project_path) {
+                        Ok(focused) => (focused, None),
+                        Err(e) => (false, Some(e)),
+                    };
+                FocusResult {
+                    session_id: session.session_id,
+                    project_path: session.project_path,
+                    focused,
+                    error,
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(serde::Serialize, Clone)]
 pub struct ScriptResult {
     pub success: bool,
     pub stdout: String,
     pub stderr: String,
     pub exit_code: Option<i32>,
+    /// The command line that ran, shell-quoted so it can be pasted into a terminal
+    pub command: String,
+    pub cwd: String,
+    pub duration_ms: u64,
+}
+
+/// How one of a new worktree's on_create_actions went
+#[derive(serde::Serialize, Clone)]
+pub struct ActionOutcome {
+    pub action_id: String,
+    /// Output of script actions
+    pub result: Option<ScriptResult>,
+    pub error: Option<String>,
+}
+
+/// Payload of the on-create-actions-finished event
+#[derive(serde::Serialize, Clone)]
+pub struct OnCreateActionsFinished {
+    pub worktree_path: String,
+    pub outcomes: Vec<ActionOutcome>,
 }
 
 #[tauri::command]
 pub async fn open_config_file() -> Result<(), String> {
-    use std::process::Command;
-
-    let config_path = config::get_config_path().ok_or("Could not determine config path")?;
+    let config_path = config::get_config_path()?;
 
     // Create parent directories and file with default content if it doesn't exist
     if !config_path.exists() {
@@ -369,7 +1673,7 @@ pub async fn open_config_file() -> Result<(), String> {
     }
 
     // Open in system default editor
-    Command::new("open")
+    shell_env::command("open")
         .arg(&config_path)
         .spawn()
         .map_err(|e| format!("Failed to open config file: {}", e))?;
@@ -384,6 +1688,22 @@ pub async fn get_config() -> Result<WoodeyeConfig, String> {
         .map_err(|e| e.to_string())?
 }
 
+/// Load the config with the overrides for the repository containing `path`.
+/// Paths outside a repository get the global settings.
+fn load_effective_config(path: &str) -> Result<EffectiveConfig, String> {
+    let config = config::load_config()?;
+    let fingerprint = git::repo_fingerprint(path).ok();
+    Ok(config.resolve(fingerprint.as_deref()))
+}
+
+/// The settings that apply to a repository after per-repo overrides
+#[tauri::command]
+pub async fn get_effective_config(repo_path: String) -> Result<EffectiveConfig, String> {
+    spawn_blocking(move || load_effective_config(&repo_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub async fn set_custom_script_path(path: Option<String>) -> Result<(), String> {
     spawn_blocking(move || {
@@ -400,28 +1720,39 @@ pub async fn run_custom_script(
     branch_name: String,
     worktree_path: String,
 ) -> Result<ScriptResult, String> {
-    use std::process::Command;
-
     // Load config to get script path
-    let config = config::load_config()?;
+    let config = load_effective_config(&worktree_path)?;
     let script_path = config
         .custom_script_path
         .ok_or("No custom script configured")?;
 
+    // Run the script with branch name as argument in the worktree directory
+    run_script(&script_path, &[branch_name], &worktree_path)
+}
+
+/// Run a script in a directory and capture its output
+fn run_script(script_path: &str, args: &[String], cwd: &str) -> Result<ScriptResult, String> {
     // Expand ~ in path
-    let expanded_path = config::expand_tilde(&script_path);
+    let expanded_path = config::expand_tilde(script_path);
 
     // Verify script exists
     if !std::path::Path::new(&expanded_path).exists() {
         return Err(format!("Script not found: {}", expanded_path));
     }
 
-    // Run the script with branch name as argument in the worktree directory
-    let output = Command::new(&expanded_path)
-        .arg(&branch_name)
-        .current_dir(&worktree_path)
+    let command = std::iter::once(expanded_path.as_str())
+        .chain(args.iter().map(String::as_str))
+        .map(terminal::shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let started = std::time::Instant::now();
+    let output = shell_env::command(&expanded_path)
+        .args(args)
+        .current_dir(cwd)
         .output()
         .map_err(|e| format!("Failed to execute script: {}", e))?;
+    let duration_ms = started.elapsed().as_millis() as u64;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -433,5 +1764,70 @@ pub async fn run_custom_script(
         stdout,
         stderr,
         exit_code,
+        command,
+        cwd: cwd.to_string(),
+        duration_ms,
+    })
+}
+
+/// Run a user-defined action from the config against a worktree. Only script
+/// actions wait for completion; they return the script's output.
+#[tauri::command]
+pub async fn run_action(
+    action_id: String,
+    worktree_path: String,
+) -> Result<Option<ScriptResult>, String> {
+    spawn_blocking(move || {
+        let config = load_effective_config(&worktree_path)?;
+        let action = config
+            .actions
+            .iter()
+            .find(|a| a.id == action_id)
+            .ok_or_else(|| format!("Unknown action: {}", action_id))?;
+        execute_action(action, &config, &worktree_path)
     })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn execute_action(
+    action: &ActionDef,
+    config: &EffectiveConfig,
+    worktree_path: &str,
+) -> Result<Option<ScriptResult>, String> {
+    let expand = |s: &str| s.replace("{path}", worktree_path);
+    match &action.kind {
+        ActionKind::Terminal {
+            terminal,
+            new_window,
+        } => terminal::open_terminal(
+            worktree_path,
+            terminal,
+            &config.terminal_commands,
+            *new_window,
+        )
+        .map(|_| None),
+        ActionKind::Editor { app } => {
+            let app = config.editor_or(app.clone())?;
+            shell_env::command("open")
+                .args(["-a", app.as_str(), worktree_path])
+                .spawn()
+                .map(|_| None)
+                .map_err(|e| format!("Failed to open {}: {}", app, e))
+        }
+        ActionKind::Script { path, args } => {
+            let args: Vec<String> = args.iter().map(|a| expand(a)).collect();
+            run_script(path, &args, worktree_path).map(Some)
+        }
+        ActionKind::Url { url } => open_with_default_app(expand(url)).map(|_| None),
+        ActionKind::Command { command } => {
+            let command = command.replace("{path}", &terminal::shell_quote(worktree_path));
+            shell_env::command("sh")
+                .args(["-c", &command])
+                .current_dir(worktree_path)
+                .spawn()
+                .map(|_| None)
+                .map_err(|e| format!("Failed to run action {}: {}", action.id, e))
+        }
+    }
 }
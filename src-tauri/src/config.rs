@@ -1,20 +1,301 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WoodeyeConfig {
     pub custom_script_path: Option<String>,
+    /// Record Claude session history to a local SQLite database
+    #[serde(default = "default_true")]
+    pub history_enabled: bool,
+    /// Custom terminal launch commands keyed by terminal name, e.g.
+    /// "wezterm": "wezterm start --cwd {path}". {path} is replaced with the
+    /// shell-quoted directory.
+    #[serde(default)]
+    pub terminal_commands: HashMap<String, String>,
+    /// Base directory for new worktrees, used when no explicit path is given
+    #[serde(default)]
+    pub worktree_base_dir: Option<String>,
+    /// Re-apply Claude hooks on startup if they are missing
+    #[serde(default)]
+    pub auto_apply_hooks: bool,
+    /// Seconds after which a session in a given state is considered stale,
+    /// keyed by state name. Overrides the built-in defaults per state.
+    #[serde(default)]
+    pub stale_thresholds: HashMap<String, u64>,
+    /// Claude hook events the installed Claude version supports. When set,
+    /// hooks are only written for these events; otherwise for all of them.
+    #[serde(default)]
+    pub supported_hook_events: Option<Vec<String>>,
+    /// Glob patterns, relative to each watched worktree, whose changes the
+    /// file watchers ignore (e.g. build output and dependency directories)
+    #[serde(default = "default_ignore_globs")]
+    pub ignore_globs: Vec<String>,
+    /// Keep the Claude status window above other windows
+    #[serde(default)]
+    pub claude_status_always_on_top: bool,
+    /// How far above other windows the Claude status window floats
+    #[serde(default)]
+    pub claude_status_level: WindowLevel,
+    /// Opacity of the Claude status window, for use as a HUD
+    #[serde(default = "default_opacity")]
+    pub claude_status_opacity: f64,
+    /// User-defined actions that can be run against a worktree
+    #[serde(default)]
+    pub actions: Vec<ActionDef>,
+    /// Actions run against every new worktree once it has been created
+    #[serde(default)]
+    pub on_create_actions: Vec<ActionRef>,
+    /// Size budget for the hunks returned by a commit or working diff. Files
+    /// that don't fit are returned truncated, without hunks.
+    #[serde(default = "default_max_diff_bytes")]
+    pub max_diff_bytes: u64,
+    /// Commit message template file, used instead of git's commit.template
+    #[serde(default)]
+    pub commit_template: Option<String>,
+    /// Settings for individual repositories, keyed by repo fingerprint
+    #[serde(default)]
+    pub per_repo: HashMap<String, RepoOverrides>,
+}
+
+/// Settings that take precedence over the global ones for one repository.
+/// Unset fields fall back to the global config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoOverrides {
+    /// Preferred terminal, e.g. "iterm"
+    #[serde(default)]
+    pub terminal: Option<String>,
+    /// Preferred editor application, e.g. "Visual Studio Code"
+    #[serde(default)]
+    pub editor: Option<String>,
+    #[serde(default)]
+    pub worktree_base_dir: Option<String>,
+    #[serde(default)]
+    pub custom_script_path: Option<String>,
+    #[serde(default)]
+    pub commit_template: Option<String>,
+    /// Merged over the global terminal_commands
+    #[serde(default)]
+    pub terminal_commands: HashMap<String, String>,
+    /// Added to the global actions, replacing any with the same id
+    #[serde(default)]
+    pub actions: Vec<ActionDef>,
+    /// Replaces the global on_create_actions
+    #[serde(default)]
+    pub on_create_actions: Option<Vec<ActionRef>>,
+}
+
+/// The global config merged with a repository's overrides
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveConfig {
+    pub terminal: Option<String>,
+    pub editor: Option<String>,
+    pub worktree_base_dir: Option<String>,
+    pub custom_script_path: Option<String>,
+    pub commit_template: Option<String>,
+    pub terminal_commands: HashMap<String, String>,
+    pub actions: Vec<ActionDef>,
+    pub on_create_actions: Vec<ActionRef>,
+}
+
+/// Terminal opened when none is chosen and the repository doesn't set one
+pub const DEFAULT_TERMINAL: &str = "terminal";
+
+impl EffectiveConfig {
+    /// The terminal to open: the one asked for, else the repository's, else the default
+    pub fn terminal_or(&self, requested: Option<String>) -> String {
+        requested
+            .or_else(|| self.terminal.clone())
+            .unwrap_or_else(|| DEFAULT_TERMINAL.to_string())
+    }
+
+    /// The editor app to open: the one asked for, else the repository's
+    pub fn editor_or(&self, requested: Option<String>) -> Result<String, String> {
+        requested
+            .or_else(|| self.editor.clone())
+            .ok_or_else(|| "No editor configured for this repository".to_string())
+    }
+}
+
+/// Stacking level of a window relative to other applications' windows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum WindowLevel {
+    #[default]
+    Normal,
+    /// Above other windows
+    Floating,
+    /// Above other windows and shown on every workspace
+    #[serde(alias = "ScreenSaver")]
+    AllWorkspaces,
+}
+
+/// Lowest opacity a window can be set to, so it can't be made invisible
+pub const MIN_WINDOW_OPACITY: f64 = 0.2;
+
+/// Limit an opacity to MIN_WINDOW_OPACITY..=1, treating NaN as opaque
+pub fn clamp_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        return 1.0;
+    }
+    opacity.clamp(MIN_WINDOW_OPACITY, 1.0)
+}
+
+/// A user-defined action, run against a worktree with `run_action`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionDef {
+    pub id: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub kind: ActionKind,
+}
+
+/// A reference to a user-defined action by its id
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionRef {
+    pub id: String,
+}
+
+/// What an action does. String parameters may contain a {path} placeholder,
+/// which is replaced with the worktree path (shell-quoted for `command`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ActionKind {
+    /// Open a terminal, as configured in terminal_commands or built in
+    Terminal {
+        terminal: String,
+        #[serde(default = "default_true")]
+        new_window: bool,
+    },
+    /// Open the worktree in an application, e.g. "Visual Studio Code". Without
+    /// an app, the repository's editor is used.
+    Editor {
+        #[serde(default)]
+        app: Option<String>,
+    },
+    /// Run an executable in the worktree and report its output
+    Script {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// Open a URL with the default browser
+    Url { url: String },
+    /// Run a shell command in the worktree without waiting for it
+    Command { command: String },
+}
+
+impl Default for WoodeyeConfig {
+    fn default() -> Self {
+        Self {
+            custom_script_path: None,
+            history_enabled: true,
+            terminal_commands: HashMap::new(),
+            worktree_base_dir: None,
+            auto_apply_hooks: false,
+            stale_thresholds: HashMap::new(),
+            supported_hook_events: None,
+            ignore_globs: default_ignore_globs(),
+            claude_status_always_on_top: false,
+            claude_status_level: WindowLevel::Normal,
+            claude_status_opacity: default_opacity(),
+            actions: Vec::new(),
+            on_create_actions: Vec::new(),
+            max_diff_bytes: default_max_diff_bytes(),
+            commit_template: None,
+            per_repo: HashMap::new(),
+        }
+    }
+}
+
+impl WoodeyeConfig {
+    /// Resolve the settings for a repository, given its fingerprint if known
+    pub fn resolve(&self, fingerprint: Option<&str>) -> EffectiveConfig {
+        let mut effective = EffectiveConfig {
+            terminal: None,
+            editor: None,
+            worktree_base_dir: self.worktree_base_dir.clone(),
+            custom_script_path: self.custom_script_path.clone(),
+            commit_template: self.commit_template.clone(),
+            terminal_commands: self.terminal_commands.clone(),
+            actions: self.actions.clone(),
+            on_create_actions: self.on_create_actions.clone(),
+        };
+        let Some(overrides) = fingerprint.and_then(|f| self.per_repo.get(f)) else {
+            return effective;
+        };
+
+        effective.terminal = overrides.terminal.clone();
+        effective.editor = overrides.editor.clone();
+        if overrides.worktree_base_dir.is_some() {
+            effective.worktree_base_dir = overrides.worktree_base_dir.clone();
+        }
+        if overrides.custom_script_path.is_some() {
+            effective.custom_script_path = overrides.custom_script_path.clone();
+        }
+        if overrides.commit_template.is_some() {
+            effective.commit_template = overrides.commit_template.clone();
+        }
+        effective.terminal_commands.extend(
+            overrides
+                .terminal_commands
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        for action in &overrides.actions {
+            effective.actions.retain(|a| a.id != action.id);
+            effective.actions.push(action.clone());
+        }
+        if let Some(on_create_actions) = &overrides.on_create_actions {
+            effective.on_create_actions = on_create_actions.clone();
+        }
+        effective
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+pub fn default_ignore_globs() -> Vec<String> {
+    ["**/node_modules/**", "**/target/**", ".git/objects/**"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+fn default_max_diff_bytes() -> u64 {
+    2 * 1024 * 1024
+}
+
+const HOME_DIR_ERROR: &str = "Could not determine home directory";
+
+fn resolve_home_dir(home: Option<PathBuf>) -> Result<PathBuf, String> {
+    home.ok_or_else(|| HOME_DIR_ERROR.to_string())
+}
+
+/// Get the user's home directory. All per-user paths (config, history, the
+/// status dir the hooks write to, Claude's settings) resolve through here so
+/// they fail the same way instead of falling back to different locations.
+pub fn home_dir() -> Result<PathBuf, String> {
+    resolve_home_dir(dirs::home_dir())
 }
 
 /// Get the path to the config file (~/.config/woodeye/config.json)
-pub fn get_config_path() -> Option<PathBuf> {
-    dirs::home_dir().map(|home| home.join(".config").join("woodeye").join("config.json"))
+pub fn get_config_path() -> Result<PathBuf, String> {
+    Ok(home_dir()?
+        .join(".config")
+        .join("woodeye")
+        .join("config.json"))
 }
 
 /// Load config from disk, returning default if file doesn't exist
 pub fn load_config() -> Result<WoodeyeConfig, String> {
-    let config_path = get_config_path().ok_or("Could not determine home directory")?;
+    let config_path = get_config_path()?;
 
     if !config_path.exists() {
         return Ok(WoodeyeConfig::default());
@@ -28,7 +309,7 @@ pub fn load_config() -> Result<WoodeyeConfig, String> {
 
 /// Save config to disk, creating directories if needed
 pub fn save_config(config: &WoodeyeConfig) -> Result<(), String> {
-    let config_path = get_config_path().ok_or("Could not determine home directory")?;
+    let config_path = get_config_path()?;
 
     // Create parent directories if they don't exist
     if let Some(parent) = config_path.parent() {
@@ -45,9 +326,135 @@ pub fn save_config(config: &WoodeyeConfig) -> Result<(), String> {
 /// Expand ~ to home directory in paths
 pub fn expand_tilde(path: &str) -> String {
     if path.starts_with("~/") {
-        if let Some(home) = dirs::home_dir() {
+        if let Ok(home) = home_dir() {
             return home.join(&path[2..]).to_string_lossy().to_string();
         }
     }
     path.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_actions() {
+        let config: WoodeyeConfig = serde_json::from_str(
+            r#"{
+                "custom_script_path": null,
+                "actions": [
+                    {"id": "code", "label": "Open in Code", "kind": "editor", "app": "Visual Studio Code"},
+                    {"id": "tab", "label": "Terminal tab", "kind": "terminal", "terminal": "iterm", "new_window": false},
+                    {"id": "pr", "label": "Open PR", "kind": "url", "url": "https://example.com/?wt={path}"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.actions.len(), 3);
+        assert!(matches!(
+            &config.actions[0].kind,
+            ActionKind::Editor { app: Some(app) } if app == "Visual Studio Code"
+        ));
+        assert!(matches!(
+            &config.actions[1].kind,
+            ActionKind::Terminal { terminal, new_window: false } if terminal == "iterm"
+        ));
+        assert!(matches!(&config.actions[2].kind, ActionKind::Url { .. }));
+    }
+
+    #[test]
+    fn test_resolve_per_repo_overrides() {
+        let config: WoodeyeConfig = serde_json::from_str(
+            r#"{
+                "custom_script_path": "~/setup.sh",
+                "worktree_base_dir": "~/worktrees",
+                "terminal_commands": {"wezterm": "wezterm start --cwd {path}"},
+                "actions": [
+                    {"id": "code", "label": "Open in Code", "kind": "editor", "app": "Visual Studio Code"},
+                    {"id": "pr", "label": "Open PR", "kind": "url", "url": "https://example.com"}
+                ],
+                "on_create_actions": [{"id": "code"}],
+                "per_repo": {
+                    "remote:github.com/owner/repo": {
+                        "terminal": "iterm",
+                        "worktree_base_dir": "~/src/repo-worktrees",
+                        "actions": [
+                            {"id": "code", "label": "Open in Zed", "kind": "editor", "app": "Zed"}
+                        ],
+                        "on_create_actions": []
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let global = config.resolve(Some("remote:github.com/other/repo"));
+        assert_eq!(global.terminal, None);
+        assert_eq!(global.worktree_base_dir.as_deref(), Some("~/worktrees"));
+        assert_eq!(global.on_create_actions.len(), 1);
+
+        let effective = config.resolve(Some("remote:github.com/owner/repo"));
+        assert_eq!(effective.terminal.as_deref(), Some("iterm"));
+        assert_eq!(
+            effective.worktree_base_dir.as_deref(),
+            Some("~/src/repo-worktrees")
+        );
+        assert_eq!(effective.custom_script_path.as_deref(), Some("~/setup.sh"));
+        assert!(effective.terminal_commands.contains_key("wezterm"));
+        assert_eq!(effective.actions.len(), 2);
+        assert_eq!(effective.actions[0].id, "pr");
+        assert_eq!(effective.actions[1].label, "Open in Zed");
+        assert!(effective.on_create_actions.is_empty());
+    }
+
+    #[test]
+    fn test_per_repo_terminal_and_editor() {
+        let config: WoodeyeConfig = serde_json::from_str(
+            r#"{
+                "custom_script_path": null,
+                "per_repo": {
+                    "remote:github.com/owner/repo": {"terminal": "ghostty", "editor": "Zed"}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let effective = config.resolve(Some("remote:github.com/owner/repo"));
+        assert_eq!(effective.terminal_or(None), "ghostty");
+        assert_eq!(effective.terminal_or(Some("warp".to_string())), "warp");
+        assert_eq!(effective.editor_or(None).as_deref(), Ok("Zed"));
+
+        let global = config.resolve(None);
+        assert_eq!(global.terminal_or(None), DEFAULT_TERMINAL);
+        assert!(global.editor_or(None).is_err());
+    }
+
+    #[test]
+    fn test_window_level_reads_old_name() {
+        let level: WindowLevel = serde_json::from_str(r#""ScreenSaver""#).unwrap();
+        assert_eq!(level, WindowLevel::AllWorkspaces);
+    }
+
+    #[test]
+    fn test_clamp_opacity() {
+        assert_eq!(clamp_opacity(0.8), 0.8);
+        assert_eq!(clamp_opacity(0.0), MIN_WINDOW_OPACITY);
+        assert_eq!(clamp_opacity(-1.0), MIN_WINDOW_OPACITY);
+        assert_eq!(clamp_opacity(3.0), 1.0);
+        assert_eq!(clamp_opacity(f64::NAN), 1.0);
+    }
+
+    #[test]
+    fn test_resolve_home_dir_missing() {
+        assert_eq!(resolve_home_dir(None), Err(HOME_DIR_ERROR.to_string()));
+    }
+
+    #[test]
+    fn test_resolve_home_dir_present() {
+        assert_eq!(
+            resolve_home_dir(Some(PathBuf::from("/home/me"))),
+            Ok(PathBuf::from("/home/me"))
+        );
+    }
+}
@@ -1,12 +1,18 @@
 use crate::types::{
-    BranchInfo, CommitDiff, CommitInfo, CreateWorktreeOptions, DiffHunk, DiffLine, DiffStats,
-    FileDiff, FileStatus, HeadInfo, PruneResult, UpstreamInfo, Worktree, WorkingDiff,
-    WorktreeStatus,
+    ApplyResult, BranchExistence, BranchInfo, BranchesContaining, ChangeCounts, CleanState,
+    CommitDiff, CommitInfo, ConflictFile, ConflictHunk, CreateOutcome, CreateWorktreeOptions,
+    DayActivity, DeleteOutcome, DeleteWorktreeResult, DiffHunk, DiffLine, DiffStats, FileBlob,
+    FileDiff, FileStatus, GcResult, GitDirs, GitOperation, HeadInfo, Identity, LfsFile,
+    OperationProgress, OperationState, PatchConflict, PruneResult, RebaseAdvice, Remote, TreeEntry,
+    UpstreamInfo, ValidationResult, WorkingDiff, Worktree, WorktreeConflict, WorktreeConflictKind,
+    WorktreeExport, WorktreeRelation, WorktreeStatus, WorktreeTargetIssue,
 };
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::{Mutex, OnceLock};
 
 /// Run a git command in the specified directory and return stdout as String
 fn run_git(path: &str, args: &[&str]) -> Result<String, String> {
@@ -25,9 +31,71 @@ fn run_git(path: &str, args: &[&str]) -> Result<String, String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
+/// Run git with `input` written to its stdin, failing on a non-zero exit
+fn run_git_with_input(path: &str, args: &[&str], input: Vec<u8>) -> Result<String, String> {
+    let output = git_output_with_input(path, args, input)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args.join(" "), stderr));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Run git with `input` written to its stdin and return the raw output, whatever
+/// the exit status. The input is written from another thread so git can't block
+/// on a full stdout pipe meanwhile.
+fn git_output_with_input(
+    path: &str,
+    args: &[&str],
+    input: Vec<u8>,
+) -> Result<std::process::Output, String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git {}: {}", args.join(" "), e))?;
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open git input")?;
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run git {}: {}", args.join(" "), e))?;
+    let _ = writer.join();
+
+    Ok(output)
+}
+
+/// Read an effective git config value, returning None if the key is unset
+fn get_config_value(path: &str, key: &str) -> Result<Option<String>, String> {
+    let output = Command::new("git")
+        .args(["-C", path, "config", "--get", key])
+        .output()
+        .map_err(|e| format!("Failed to run git config: {}", e))?;
+
+    // git config exits with 1 when the key isn't set
+    if output.status.code() == Some(1) {
+        return Ok(None);
+    }
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git config --get {} failed: {}", key, stderr));
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(if value.is_empty() { None } else { Some(value) })
+}
+
 /// Get upstream tracking info for the current branch
 /// Returns None if branch has no upstream or git command fails
-fn get_upstream_info(path_str: &str) -> Option<UpstreamInfo> {
+pub fn get_upstream_info(path_str: &str) -> Option<UpstreamInfo> {
     // Get upstream branch name: git rev-parse --abbrev-ref @{upstream}
     let remote_branch = run_git(path_str, &["rev-parse", "--abbrev-ref", "@{upstream}"]).ok()?;
     let remote_branch = remote_branch.trim().to_string();
@@ -62,35 +130,140 @@ fn parse_ahead_behind(output: &str) -> (u32, u32) {
     }
 }
 
-pub fn get_all_worktrees(repo_path: &str) -> Result<Vec<Worktree>, String> {
-    // Use git worktree list --porcelain to get all worktrees
+/// List all worktrees, main worktree first
+fn list_worktree_records(repo_path: &str) -> Result<Vec<WorktreeRecord>, String> {
     let output = run_git(repo_path, &["worktree", "list", "--porcelain"])?;
+    Ok(parse_worktree_porcelain(&output))
+}
+
+/// List the paths of all worktrees, main worktree first
+fn list_worktree_paths(repo_path: &str) -> Result<Vec<PathBuf>, String> {
+    Ok(list_worktree_records(repo_path)?
+        .into_iter()
+        .map(|r| r.path)
+        .collect())
+}
 
-    // Parse porcelain output to extract paths
-    // Format:
-    // worktree /path/to/main
-    // HEAD abc1234...
-    // branch refs/heads/main
-    //
-    // worktree /path/to/linked
-    // HEAD def5678...
-    // branch refs/heads/feature
+/// One record of `git worktree list --porcelain`
+#[derive(Debug, Default, PartialEq)]
+struct WorktreeRecord {
+    path: PathBuf,
+    head: Option<String>,
+    /// Short branch name, None when detached
+    branch: Option<String>,
+    bare: bool,
+    detached: bool,
+    locked: bool,
+    /// Why git considers the worktree prunable (e.g. its directory is gone)
+    prunable: Option<String>,
+}
 
-    let mut worktree_paths: Vec<PathBuf> = Vec::new();
+/// Parse `git worktree list --porcelain` output. Records are separated by
+/// blank lines; boolean attributes appear as bare labels, optionally
+/// followed by a reason (e.g. "locked moved to external drive"). Unlike the
+/// human-readable format, paths are printed verbatim, spaces included.
+///
+/// worktree /path/to/main
+/// HEAD abc1234...
+/// branch refs/heads/main
+///
+/// worktree /path/to/linked
+/// HEAD def5678...
+/// detached
+fn parse_worktree_porcelain(output: &str) -> Vec<WorktreeRecord> {
+    let mut records = Vec::new();
+    let mut current: Option<WorktreeRecord> = None;
 
     for line in output.lines() {
         if let Some(path) = line.strip_prefix("worktree ") {
-            worktree_paths.push(PathBuf::from(path));
+            records.extend(current.take());
+            current = Some(WorktreeRecord {
+                path: PathBuf::from(path),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let Some(record) = current.as_mut() else {
+            continue;
+        };
+        let (label, value) = line.split_once(' ').unwrap_or((line, ""));
+        match label {
+            "HEAD" => record.head = Some(value.to_string()),
+            "branch" => {
+                let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                record.branch = Some(branch.to_string());
+            }
+            "bare" => record.bare = true,
+            "detached" => record.detached = true,
+            "locked" => record.locked = true,
+            "prunable" => record.prunable = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    records.extend(current);
+    records
+}
+
+/// Find worktrees that share a branch or whose gitdir link is broken, as can
+/// happen after moving or deleting worktree directories by hand
+pub fn worktree_conflicts(repo_path: &str) -> Result<Vec<WorktreeConflict>, String> {
+    let output = run_git(repo_path, &["worktree", "list", "--porcelain"])?;
+    let records = parse_worktree_porcelain(&output);
+    Ok(find_worktree_conflicts(&records, |path| {
+        path.join(".git").exists()
+    }))
+}
+
+fn find_worktree_conflicts(
+    records: &[WorktreeRecord],
+    has_gitdir: impl Fn(&Path) -> bool,
+) -> Vec<WorktreeConflict> {
+    let mut conflicts: Vec<WorktreeConflict> = Vec::new();
+
+    for record in records {
+        let Some(branch) = &record.branch else {
+            continue;
+        };
+        let existing = conflicts
+            .iter_mut()
+            .find(|c| c.branch.as_ref() == Some(branch));
+        match existing {
+            Some(conflict) => conflict.paths.push(record.path.clone()),
+            None => conflicts.push(WorktreeConflict {
+                kind: WorktreeConflictKind::DuplicateBranch,
+                branch: Some(branch.clone()),
+                paths: vec![record.path.clone()],
+            }),
+        }
+    }
+    // Every branch got an entry above; only shared ones are conflicts
+    conflicts.retain(|c| c.paths.len() > 1);
+
+    for record in records {
+        if !record.bare && (record.prunable.is_some() || !has_gitdir(&record.path)) {
+            conflicts.push(WorktreeConflict {
+                kind: WorktreeConflictKind::MissingGitdir,
+                branch: record.branch.clone(),
+                paths: vec![record.path.clone()],
+            });
         }
     }
 
+    conflicts
+}
+
+pub fn get_all_worktrees(repo_path: &str) -> Result<Vec<Worktree>, String> {
+    let records = list_worktree_records(repo_path)?;
+
     // Process all worktrees in parallel using rayon
-    let mut worktrees: Vec<Worktree> = worktree_paths
+    let mut worktrees: Vec<Worktree> = records
         .par_iter()
         .enumerate()
-        .filter_map(|(idx, path)| {
+        .filter_map(|(idx, record)| {
             let is_main = idx == 0; // First worktree is the main one
-            build_worktree_info(path, is_main).ok()
+            build_worktree_info(repo_path, record, is_main).ok()
         })
         .collect();
 
@@ -100,36 +273,94 @@ pub fn get_all_worktrees(repo_path: &str) -> Result<Vec<Worktree>, String> {
     Ok(worktrees)
 }
 
+/// Get the info for one worktree of a repository, e.g. to refresh a single row
+/// after acting on it. Relative paths are resolved against the repository.
+pub fn get_worktree(repo_path: &str, worktree_path: &str) -> Result<Worktree, String> {
+    let path = Path::new(repo_path).join(worktree_path);
+    let path = path.canonicalize().unwrap_or(path);
+
+    let records = list_worktree_records(repo_path)?;
+    let (idx, record) = records
+        .iter()
+        .enumerate()
+        .find(|(_, r)| r.path.canonicalize().unwrap_or_else(|_| r.path.clone()) == path)
+        .ok_or_else(|| format!("{} is not a worktree of {}", path.display(), repo_path))?;
+    build_worktree_info(repo_path, record, idx == 0)
+}
+
+/// Describe a repository's worktrees, with their status, its remotes and
+/// default branch as pretty-printed JSON, e.g. to recreate the layout later
+pub fn export_worktrees(repo_path: &str) -> Result<String, String> {
+    let mut worktrees = get_all_worktrees(repo_path)?;
+    worktrees.par_iter_mut().for_each(|worktree| {
+        if worktree.exists_on_disk && !worktree.is_bare {
+            worktree.status = get_worktree_status(&worktree.path.to_string_lossy()).ok();
+        }
+    });
+
+    let export = WorktreeExport {
+        repo_path: PathBuf::from(repo_path),
+        default_branch: default_branch(repo_path, "origin", false)?,
+        remotes: list_remotes(repo_path)?,
+        worktrees,
+    };
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize worktrees: {}", e))
+}
+
 /// Get status for a single worktree path (for lazy loading)
 pub fn get_worktree_status_by_path(worktree_path: &str) -> Result<WorktreeStatus, String> {
     get_worktree_status(worktree_path)
 }
 
-fn build_worktree_info(path: &PathBuf, is_main: bool) -> Result<Worktree, String> {
+fn build_worktree_info(
+    repo_path: &str,
+    record: &WorktreeRecord,
+    is_main: bool,
+) -> Result<Worktree, String> {
+    let path = &record.path;
     let path_str = path.to_string_lossy();
 
-    // Get short SHA
-    let short_sha = run_git(&path_str, &["rev-parse", "--short", "HEAD"])?
-        .trim()
-        .to_string();
+    // The directory was removed without `git worktree remove`; git can't run
+    // there, so report what the porcelain record knows so it can be pruned
+    if !record.bare && !path.exists() {
+        return Ok(missing_worktree_info(repo_path, record, is_main));
+    }
 
-    // Get branch name (returns "HEAD" if detached)
-    let branch_output = run_git(&path_str, &["rev-parse", "--abbrev-ref", "HEAD"])?;
-    let branch_name = branch_output.trim();
-    let branch = if branch_name == "HEAD" {
-        None // Detached HEAD
-    } else {
-        Some(branch_name.to_string())
+    // Branch and HEAD come straight from the porcelain record (branch is None if
+    // detached). Bare repositories have no HEAD line, so ask git directly.
+    let (branch, head) = match &record.head {
+        Some(head) => (record.branch.clone(), head.clone()),
+        None => (
+            run_git(&path_str, &["symbolic-ref", "--short", "-q", "HEAD"])
+                .ok()
+                .map(|b| b.trim().to_string()),
+            run_git(&path_str, &["rev-parse", "HEAD"])?
+                .trim()
+                .to_string(),
+        ),
     };
 
-    // Get commit message summary
-    let commit_message = run_git(&path_str, &["log", "-1", "--format=%s"])?
-        .trim()
-        .to_string();
-
-    // Get commit timestamp
-    let timestamp_str = run_git(&path_str, &["log", "-1", "--format=%ct"])?;
-    let timestamp = timestamp_str.trim().parse::<i64>().unwrap_or(0);
+    // Abbreviated SHA (as long as core.abbrev and uniqueness require),
+    // commit timestamp and summary
+    let log = run_git(&path_str, &["log", "-1", "--format=%h%x1f%ct%x1f%s", &head])?;
+    let mut fields = log.trim_end_matches('\n').splitn(3, '\x1f');
+    let short_sha = fields.next().unwrap_or(&head).to_string();
+    let timestamp = fields
+        .next()
+        .and_then(|t| t.parse::<i64>().ok())
+        .unwrap_or(0);
+    let commit_message = fields.next().unwrap_or("").to_string();
+
+    // Approximate recent activity with the worktree root's mtime, which changes
+    // whenever top-level entries are added, removed or renamed (including editor
+    // swap files); a full tree walk would be too slow for large worktrees
+    let dir_mtime = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
 
     // Defer status scanning - return None initially for faster load
     // Frontend will fetch status lazily
@@ -158,12 +389,58 @@ fn build_worktree_info(path: &PathBuf, is_main: bool) -> Result<Worktree, String
         },
         status,
         last_commit_timestamp: timestamp,
+        last_modified: timestamp.max(dir_mtime),
+        is_bare: record.bare,
+        is_detached: record.detached,
+        is_locked: record.locked,
+        exists_on_disk: true,
     })
 }
 
+fn missing_worktree_info(repo_path: &str, record: &WorktreeRecord, is_main: bool) -> Worktree {
+    let head = record.head.clone().unwrap_or_default();
+    // Abbreviated by the repository, as git can't run in the missing directory
+    let short_sha = run_git(repo_path, &["rev-parse", "--short", &head])
+        .map(|sha| sha.trim().to_string())
+        .unwrap_or(head);
+    Worktree {
+        path: record.path.clone(),
+        name: record
+            .path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        is_main,
+        head: HeadInfo {
+            branch: record.branch.clone(),
+            commit_sha: short_sha,
+            commit_message: String::new(),
+            upstream: None,
+        },
+        status: None,
+        last_commit_timestamp: 0,
+        last_modified: 0,
+        is_bare: false,
+        is_detached: record.detached,
+        is_locked: record.locked,
+        exists_on_disk: false,
+    }
+}
+
 fn get_worktree_status(worktree_path: &str) -> Result<WorktreeStatus, String> {
     let output = run_git(worktree_path, &["status", "--porcelain"])?;
-    Ok(parse_status_porcelain(&output))
+    let mut status = parse_status_porcelain(&output);
+    let git_dir = gitdir_for_worktree(worktree_path)?.git_dir;
+    status.operation = operation_progress_from_git_dir(&git_dir);
+    Ok(status)
+}
+
+/// Count staged, unstaged and untracked changes with a single status call.
+/// Cheaper than the full WorktreeStatus for badges that refresh often.
+pub fn count_changes(worktree_path: &str) -> Result<ChangeCounts, String> {
+    let output = run_git(worktree_path, &["status", "--porcelain"])?;
+    Ok(parse_change_counts(&output))
 }
 
 // Get commit history for a worktree
@@ -173,7 +450,7 @@ pub fn get_commit_history(
     offset: usize,
 ) -> Result<Vec<CommitInfo>, String> {
     // Use record separator (%x1e) between commits and unit separator (%x1f) between fields
-    // Format: hash, short_hash, author_name, author_email, timestamp, summary, body
+    // Format: hash, short_hash, author_name, author_email, commit_timestamp, summary, body
     let format = "%H%x1f%h%x1f%an%x1f%ae%x1f%ct%x1f%s%x1f%B%x1e";
 
     let output = run_git(
@@ -189,148 +466,936 @@ pub fn get_commit_history(
     Ok(parse_commit_log(&output))
 }
 
-// Get diff for a specific commit
-pub fn get_commit_diff(worktree_path: &str, commit_sha: &str) -> Result<CommitDiff, String> {
-    // Get commit info using git log
-    let format = "%H%x1f%h%x1f%an%x1f%ae%x1f%ct%x1f%s%x1f%B";
-    let commit_output = run_git(
+// Get the last few commits as `git log --oneline` text, for compact tooltips
+pub fn get_log_summary(worktree_path: &str, count: usize) -> Result<String, String> {
+    run_git(
         worktree_path,
-        &["log", "-1", &format!("--format={}", format), commit_sha],
+        &["log", "--oneline", &format!("-n{}", count)],
+    )
+}
+
+/// Count the commits made since `since` (unix seconds, by committer date) per
+/// local day, oldest first. Days without commits are left out.
+pub fn commit_activity(worktree_path: &str, since: i64) -> Result<Vec<DayActivity>, String> {
+    let output = run_git(
+        worktree_path,
+        &[
+            "log",
+            &format!("--since=@{}", since),
+            "--date=format-local:%Y-%m-%d",
+            "--format=%x1e%cd",
+            "--numstat",
+        ],
     )?;
+    Ok(parse_commit_activity(&output))
+}
 
-    let fields: Vec<&str> = commit_output.trim().split('\x1f').collect();
-    if fields.len() < 6 {
-        return Err(format!("Failed to parse commit info for {}", commit_sha));
+/// Parse `git log --numstat` output where each commit starts with a record
+/// separator and its date. Binary files ("-" counts) add no lines.
+fn parse_commit_activity(output: &str) -> Vec<DayActivity> {
+    let mut days: BTreeMap<&str, DayActivity> = BTreeMap::new();
+    for record in output.split('\x1e').filter(|r| !r.trim().is_empty()) {
+        let mut lines = record.lines();
+        let Some(date) = lines.next().map(str::trim) else {
+            continue;
+        };
+        let day = days.entry(date).or_insert_with(|| DayActivity {
+            date: date.to_string(),
+            commits: 0,
+            insertions: 0,
+            deletions: 0,
+        });
+        day.commits += 1;
+        for line in lines {
+            let mut fields = line.split('\t');
+            let (Some(added), Some(deleted)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            day.insertions += added.parse::<u32>().unwrap_or(0);
+            day.deletions += deleted.parse::<u32>().unwrap_or(0);
+        }
     }
+    days.into_values().collect()
+}
 
-    let commit_info = CommitInfo {
-        hash: fields[0].to_string(),
-        short_hash: fields[1].to_string(),
-        author_name: fields[2].to_string(),
-        author_email: fields[3].to_string(),
-        timestamp: fields[4].parse::<i64>().unwrap_or(0),
-        summary: fields[5].to_string(),
-        message: fields.get(6).unwrap_or(&"").trim().to_string(),
+/// Get the effective commit identity, including repo-local overrides
+pub fn get_identity(worktree_path: &str) -> Result<Identity, String> {
+    Ok(Identity {
+        name: get_config_value(worktree_path, "user.name")?,
+        email: get_config_value(worktree_path, "user.email")?,
+    })
+}
+
+/// Set the commit identity for a worktree.
+///
+/// Worktrees of the same repository share its config, so by default this writes
+/// the common repo config and affects every worktree. With `per_worktree`, the
+/// repo's `extensions.worktreeConfig` is enabled and the identity is written to
+/// this worktree's own config.worktree instead.
+pub fn set_worktree_identity(
+    worktree_path: &str,
+    name: &str,
+    email: &str,
+    per_worktree: bool,
+) -> Result<Identity, String> {
+    let scope = if per_worktree {
+        run_git(
+            worktree_path,
+            &["config", "--local", "extensions.worktreeConfig", "true"],
+        )?;
+        "--worktree"
+    } else {
+        "--local"
     };
 
-    // Get diff using git show
-    let diff_output = run_git(
-        worktree_path,
-        &["show", commit_sha, "--format=", "-U3", "-M"],
-    )?;
+    run_git(worktree_path, &["config", scope, "user.name", name])?;
+    run_git(worktree_path, &["config", scope, "user.email", email])?;
 
-    let files = parse_git_diff_output(&diff_output);
+    get_identity(worktree_path)
+}
 
-    // Calculate stats
-    let mut total_insertions = 0u32;
-    let mut total_deletions = 0u32;
+/// Read the commit message template, from `template_override` (the woodeye
+/// config) if set, else git's commit.template. Relative paths are resolved
+/// against the worktree. None when no template is configured.
+pub fn get_commit_template(
+    worktree_path: &str,
+    template_override: Option<&str>,
+) -> Result<Option<String>, String> {
+    let path = match template_override {
+        Some(path) => crate::config::expand_tilde(path),
+        // --path expands ~ like git itself does when committing
+        None => {
+            let args = ["config", "--path", "--get", "commit.template"];
+            match run_git(worktree_path, &args) {
+                Ok(path) => path.trim().to_string(),
+                Err(_) => return Ok(None),
+            }
+        }
+    };
+    let path = Path::new(worktree_path).join(path);
+    fs::read_to_string(&path)
+        .map(Some)
+        .map_err(|e| format!("Failed to read commit template {}: {}", path.display(), e))
+}
 
-    for file in &files {
-        for hunk in &file.hunks {
-            for line in &hunk.lines {
-                match line.kind {
-                    '+' => total_insertions += 1,
-                    '-' => total_deletions += 1,
-                    _ => {}
-                }
+/// Commit the staged changes. Comment lines (e.g. left over from a template)
+/// are stripped, and each trailer ("Key: value") is appended to the message.
+pub fn commit(
+    worktree_path: &str,
+    message: &str,
+    trailers: &[String],
+) -> Result<CommitInfo, String> {
+    let mut args = vec!["commit", "--cleanup=strip", "-F", "-"];
+    for trailer in trailers {
+        args.extend(["--trailer", trailer.as_str()]);
+    }
+    run_git_with_input(worktree_path, &args, message.as_bytes().to_vec())?;
+    get_commit_info(worktree_path, "HEAD")
+}
+
+/// Replace the last commit's message, leaving its changes (and anything
+/// staged) alone. Refuses to rewrite a commit already on a remote unless
+/// `force` is set.
+pub fn reword_head(
+    worktree_path: &str,
+    new_message: &str,
+    force: bool,
+) -> Result<CommitInfo, String> {
+    if !force {
+        if let Some(remote_branch) = remote_branch_containing(worktree_path, "HEAD")? {
+            return Err(format!(
+                "The last commit is already pushed to {}; rewording it would rewrite shared history",
+                remote_branch
+            ));
+        }
+    }
+
+    // --only without paths amends the message but none of the staged changes
+    run_git_with_input(
+        worktree_path,
+        &["commit", "--amend", "--only", "--cleanup=strip", "-F", "-"],
+        new_message.as_bytes().to_vec(),
+    )?;
+    get_commit_info(worktree_path, "HEAD")
+}
+
+/// Squash the last `count` commits into one with `message`. Refuses if any of
+/// them is already on a remote unless `force` is set, and if changes are
+/// staged, since they would end up in the squashed commit.
+pub fn squash_head(
+    worktree_path: &str,
+    count: u32,
+    message: &str,
+    force: bool,
+) -> Result<CommitInfo, String> {
+    if count < 2 {
+        return Err("Squashing needs at least 2 commits".to_string());
+    }
+    // HEAD~n walks first parents, so only those count towards the history.
+    // The commit before the squashed ones is kept, so the root can't be squashed.
+    let first_parents: u32 = run_git(
+        worktree_path,
+        &["rev-list", "--count", "--first-parent", "HEAD"],
+    )?
+    .trim()
+    .parse()
+    .map_err(|e| format!("Failed to count commits: {}", e))?;
+    let mut limit = first_parents.saturating_sub(1);
+    // On another branch, stay within its own commits. Nothing is ahead of the
+    // default branch itself, so there the whole first-parent history counts.
+    if let Ok(base) = resolve_base(worktree_path, None) {
+        let branch = run_git(worktree_path, &["symbolic-ref", "-q", "--short", "HEAD"]).ok();
+        if branch.as_deref().map(str::trim) != Some(base.as_str()) {
+            if let Ok(ahead) = commits_ahead_of(worktree_path, Some(&base)) {
+                limit = limit.min(ahead);
             }
         }
     }
+    if count > limit {
+        return Err(format!(
+            "Can squash at most {} commits on this branch",
+            limit
+        ));
+    }
+
+    // Later commits contain the oldest one, so checking it covers them all
+    let oldest = format!("HEAD~{}", count - 1);
+    if !force {
+        if let Some(remote_branch) = remote_branch_containing(worktree_path, &oldest)? {
+            return Err(format!(
+                "Some of these commits are already pushed to {}; squashing them would rewrite shared history",
+                remote_branch
+            ));
+        }
+    }
+    if run_git(worktree_path, &["diff", "--cached", "--quiet"]).is_err() {
+        return Err("Unstage your changes before squashing".to_string());
+    }
+
+    let original_head = run_git(worktree_path, &["rev-parse", "HEAD"])?;
+    let parent = format!("HEAD~{}", count);
+    run_git(worktree_path, &["reset", "--soft", &parent])?;
+    let committed = run_git_with_input(
+        worktree_path,
+        &["commit", "--cleanup=strip", "-F", "-"],
+        message.as_bytes().to_vec(),
+    );
+    if let Err(e) = committed {
+        // Put the original commits back rather than leave them uncommitted
+        run_git(worktree_path, &["reset", "--soft", original_head.trim()])?;
+        return Err(e);
+    }
+    get_commit_info(worktree_path, "HEAD")
+}
+
+/// The first remote-tracking branch containing `rev`, i.e. whether it's pushed
+fn remote_branch_containing(worktree_path: &str, rev: &str) -> Result<Option<String>, String> {
+    let format = "--format=%(refname:short)";
+    let remotes = run_git(worktree_path, &["branch", "-r", "--contains", rev, format])?;
+    Ok(remotes.lines().find(|l| !l.is_empty()).map(str::to_string))
+}
+
+// Get diff for a specific commit
+pub fn get_commit_diff(
+    worktree_path: &str,
+    commit_sha: &str,
+    max_diff_bytes: u64,
+    ignore_whitespace: bool,
+) -> Result<CommitDiff, String> {
+    let commit_info = get_commit_info(worktree_path, commit_sha)?;
+
+    // Get diff using git show
+    let mut args = vec!["show", commit_sha, "--format=", "-U3", "-M"];
+    if ignore_whitespace {
+        args.push("--ignore-all-space");
+    }
+    let diff_output = run_git(worktree_path, &args)?;
 
-    let files_changed = files.len() as u32;
+    let mut files = parse_git_diff_output(&diff_output);
+    let stats = sum_diff_stats(&files);
+    mark_lfs_files(worktree_path, &mut files);
+    truncate_large_diffs(&mut files, max_diff_bytes);
 
     Ok(CommitDiff {
         commit: commit_info,
         files,
-        stats: DiffStats {
-            files_changed,
-            insertions: total_insertions,
-            deletions: total_deletions,
-        },
+        stats,
     })
 }
 
-/// Generate synthetic diff hunks for a new/untracked file
-/// Returns (hunks, is_binary) - empty hunks if binary or read fails
-fn generate_new_file_hunks(file_path: &Path) -> (Vec<DiffHunk>, bool) {
-    // Read file content
-    let content = match fs::read(file_path) {
-        Ok(bytes) => bytes,
-        Err(_) => return (Vec::new(), false),
-    };
+/// Get what a worktree's branch adds: the diff from where it diverged from
+/// `base` (by default the remote's default branch) to HEAD. The returned
+/// commit is the merge base.
+pub fn branch_diff_from_base(
+    worktree_path: &str,
+    base: Option<&str>,
+    max_diff_bytes: u64,
+) -> Result<CommitDiff, String> {
+    let base = resolve_base(worktree_path, base)?;
+    let merge_base = merge_base(worktree_path, &base, "HEAD")?;
+    let commit_info = get_commit_info(worktree_path, &merge_base)?;
+
+    let diff_output = run_git(worktree_path, &["diff", "-U3", "-M", &merge_base, "HEAD"])?;
+    let mut files = parse_git_diff_output(&diff_output);
+    let stats = sum_diff_stats(&files);
+    mark_lfs_files(worktree_path, &mut files);
+    truncate_large_diffs(&mut files, max_diff_bytes);
 
-    // Check if binary by looking for null bytes in first 8KB
-    let check_len = content.len().min(8192);
-    if content[..check_len].contains(&0) {
-        return (Vec::new(), true);
-    }
+    Ok(CommitDiff {
+        commit: commit_info,
+        files,
+        stats,
+    })
+}
 
-    // Convert to string
-    let text = match String::from_utf8(content) {
-        Ok(s) => s,
-        Err(_) => return (Vec::new(), true), // Non-UTF8 treated as binary
-    };
+/// Count the commits on HEAD that aren't on `base` (by default the remote's
+/// default branch), i.e. the work done on the current branch. 0 when HEAD is
+/// an ancestor of the base.
+pub fn commits_ahead_of(worktree_path: &str, base: Option<&str>) -> Result<u32, String> {
+    let range = format!("{}..HEAD", resolve_base(worktree_path, base)?);
+    let output = run_git(worktree_path, &["rev-list", "--count", &range])?;
+    output
+        .trim()
+        .parse()
+        .map_err(|e| format!("Failed to count commits: {}", e))
+}
 
-    // Split into lines
-    let lines: Vec<&str> = text.lines().collect();
-    let line_count = lines.len() as u32;
+/// Check whether `base` (by default the remote's default branch) has moved on
+/// since HEAD branched off it
+pub fn needs_rebase(worktree_path: &str, base: Option<&str>) -> Result<RebaseAdvice, String> {
+    let base = resolve_base(worktree_path, base)?;
+    let base_tip = run_git(
+        worktree_path,
+        &["rev-parse", "--verify", &format!("{}^{{commit}}", base)],
+    )?;
+    let merge_base = run_git(worktree_path, &["merge-base", "HEAD", &base]).ok();
 
-    if line_count == 0 {
-        return (Vec::new(), false);
+    let range = format!("HEAD..{}", base);
+    let behind = run_git(worktree_path, &["rev-list", "--count", &range])?
+        .trim()
+        .parse()
+        .map_err(|e| format!("Failed to count commits: {}", e))?;
+
+    Ok(RebaseAdvice {
+        base_moved: merge_base.as_deref().map(str::trim) != Some(base_tip.trim()),
+        behind,
+        base,
+    })
+}
+
+/// The base to compare a branch against: `base` if given, otherwise origin's
+/// default branch, preferring the local branch over the remote-tracking one
+fn resolve_base(worktree_path: &str, base: Option<&str>) -> Result<String, String> {
+    if let Some(base) = base {
+        // Callers put the base into ranges, where it mustn't read as an option
+        verify_commit(worktree_path, base)?;
+        return Ok(base.to_string());
+    }
+    let branch = default_branch(worktree_path, "origin", false)?
+        .ok_or("Could not determine the default branch, choose a base branch")?;
+    let local = format!("refs/heads/{}", branch);
+    if run_git(worktree_path, &["rev-parse", "-q", "--verify", &local]).is_ok() {
+        Ok(branch)
+    } else {
+        Ok(format!("origin/{}", branch))
     }
+}
 
-    // Create diff lines (all additions)
-    let diff_lines: Vec<DiffLine> = lines
-        .into_iter()
-        .map(|line| DiffLine {
-            kind: '+',
-            content: line.to_string(),
+/// Compare every worktree's HEAD with the default branch: where it diverged
+/// and how far ahead and behind it is. Bare entries are skipped.
+pub fn worktree_relationships(repo_path: &str) -> Result<Vec<WorktreeRelation>, String> {
+    let base = resolve_base(repo_path, None)?;
+    let records = list_worktree_records(repo_path)?;
+
+    // HEADs come from the porcelain records, so everything runs in the repo
+    // and works for worktrees whose directory is missing
+    Ok(records
+        .par_iter()
+        .filter_map(|record| {
+            let head = record.head.as_deref()?;
+            let merge_base = merge_base(repo_path, &base, head).ok();
+            let range = format!("{}...{}", base, head);
+            let (behind, ahead) =
+                run_git(repo_path, &["rev-list", "--count", "--left-right", &range])
+                    .map(|s| parse_ahead_behind(&s))
+                    .unwrap_or((0, 0));
+            Some(WorktreeRelation {
+                path: record.path.clone(),
+                branch: record.branch.clone(),
+                merge_base,
+                ahead,
+                behind,
+            })
         })
-        .collect();
+        .collect())
+}
 
-    // Create single hunk for the entire file
-    let hunk = DiffHunk {
-        old_start: 0,
-        old_lines: 0,
-        new_start: 1,
-        new_lines: line_count,
-        header: format!("@@ -0,0 +1,{} @@", line_count),
-        lines: diff_lines,
+/// Get the best common ancestor of two revisions
+fn merge_base(worktree_path: &str, a: &str, b: &str) -> Result<String, String> {
+    let a_sha = verify_commit(worktree_path, a)?;
+    let b_sha = verify_commit(worktree_path, b)?;
+    let output = run_git(worktree_path, &["merge-base", &a_sha, &b_sha])
+        .map_err(|_| format!("{} and {} have no common history", a, b))?;
+    Ok(output.trim().to_string())
+}
+
+/// Get the full diff of a single file in a commit, or in the working tree
+/// (staged or unstaged) when `commit_sha` is None. Not subject to the diff
+/// size limit, so a truncated file can be loaded on request.
+pub fn get_file_diff(
+    worktree_path: &str,
+    file_path: &str,
+    commit_sha: Option<&str>,
+    staged: bool,
+    ignore_whitespace: bool,
+) -> Result<Option<FileDiff>, String> {
+    // The SHA comes from the caller, so it mustn't be read as an option
+    let commit = commit_sha
+        .map(|sha| verify_commit(worktree_path, sha))
+        .transpose()?;
+    let mut args = match commit.as_deref() {
+        Some(sha) => vec!["show", sha, "--format=", "-U3", "-M"],
+        None if staged => vec!["diff", "--cached", "-U3"],
+        None => vec!["diff", "-U3"],
     };
+    if ignore_whitespace {
+        args.push("--ignore-all-space");
+    }
+    args.extend(["--", file_path]);
+    let mut diff: Vec<FileDiff> = parse_git_diff_output(&run_git(worktree_path, &args)?)
+        .into_iter()
+        .take(1)
+        .collect();
+    mark_lfs_files(worktree_path, &mut diff);
+    if !diff.is_empty() || commit_sha.is_some() || staged {
+        return Ok(diff.pop());
+    }
 
-    (vec![hunk], false)
+    // Untracked files don't show up in `git diff`
+    let untracked = run_git(
+        worktree_path,
+        &["ls-files", "-o", "--exclude-standard", "--", file_path],
+    )?;
+    if untracked.trim().is_empty() {
+        return Ok(None);
+    }
+    let (hunks, binary) = generate_new_file_hunks(&Path::new(worktree_path).join(file_path));
+    let mut diff = vec![FileDiff {
+        path: file_path.to_string(),
+        status: FileStatus::Added,
+        old_path: None,
+        hunks,
+        binary,
+        truncated: false,
+        is_lfs: false,
+    }];
+    mark_lfs_files(worktree_path, &mut diff);
+    Ok(diff.pop())
 }
 
-// Get uncommitted working directory changes using git CLI
-pub fn get_working_diff(worktree_path: &str) -> Result<WorkingDiff, String> {
-    // Get staged changes: git diff --cached
-    let staged_diff_text = run_git(worktree_path, &["diff", "--cached", "-U3"])?;
-    let staged_files = parse_git_diff_output(&staged_diff_text);
-
-    // Get unstaged changes: git diff
-    let unstaged_diff_text = run_git(worktree_path, &["diff", "-U3"])?;
-    let mut unstaged_files = parse_git_diff_output(&unstaged_diff_text);
+/// Replace a file in the working tree and index with its version at
+/// `source_ref`. Refuses to overwrite uncommitted changes to the file unless
+/// `force` is set. Returns the updated working diff.
+pub fn checkout_file(
+    worktree_path: &str,
+    source_ref: &str,
+    file_path: &str,
+    force: bool,
+    max_diff_bytes: u64,
+) -> Result<WorkingDiff, String> {
+    let commit = format!("{}^{{commit}}", source_ref);
+    if source_ref.starts_with('-')
+        || run_git(worktree_path, &["rev-parse", "-q", "--verify", &commit]).is_err()
+    {
+        return Err(format!("Unknown commit or branch: {}", source_ref));
+    }
 
-    // Get untracked files: git ls-files --others --exclude-standard
-    let untracked_text = run_git(worktree_path, &["ls-files", "--others", "--exclude-standard"])?;
-    let worktree_dir = Path::new(worktree_path);
-    for line in untracked_text.lines() {
-        if !line.is_empty() {
-            let file_path = worktree_dir.join(line);
-            let (hunks, binary) = generate_new_file_hunks(&file_path);
-            unstaged_files.push(FileDiff {
-                path: line.to_string(),
-                status: FileStatus::Added,
-                old_path: None,
-                hunks,
-                binary,
-            });
+    if !force {
+        let status = run_git(worktree_path, &["status", "--porcelain", "--", file_path])?;
+        if !status.trim().is_empty() {
+            return Err(format!("{} has uncommitted changes", file_path));
         }
     }
 
-    // Calculate total stats
-    let mut total_insertions = 0u32;
-    let mut total_deletions = 0u32;
+    run_git(worktree_path, &["checkout", source_ref, "--", file_path])?;
+    get_working_diff(worktree_path, true, max_diff_bytes, false)
+}
+
+/// Get a single file's staged or unstaged changes, including an untracked
+/// file's whole content. A file without changes gets a diff with no hunks.
+pub fn get_working_diff_for_file(
+    worktree_path: &str,
+    file_path: &str,
+    staged: bool,
+) -> Result<FileDiff, String> {
+    let diff = get_file_diff(worktree_path, file_path, None, staged, false)?;
+    Ok(diff.unwrap_or_else(|| FileDiff {
+        path: file_path.to_string(),
+        status: FileStatus::Modified,
+        old_path: None,
+        hunks: Vec::new(),
+        binary: false,
+        truncated: false,
+        is_lfs: false,
+    }))
+}
+
+/// Mark files that Git LFS manages and drop their hunks, which would only
+/// show the pointer file. Attributes are read from the worktree.
+fn mark_lfs_files(worktree_path: &str, files: &mut [FileDiff]) {
+    if files.is_empty() {
+        return;
+    }
+    let input: Vec<u8> = files
+        .iter()
+        .flat_map(|f| f.path.bytes().chain([0]))
+        .collect();
+    let Ok(output) = run_git_with_input(
+        worktree_path,
+        &["check-attr", "-z", "--stdin", "filter"],
+        input,
+    ) else {
+        return;
+    };
+
+    let lfs_paths = parse_lfs_check_attr(&output);
+    for file in files.iter_mut().filter(|f| lfs_paths.contains(&f.path)) {
+        file.is_lfs = true;
+        file.hunks.clear();
+    }
+}
+
+/// List the files Git LFS tracks in a worktree and whether their objects have
+/// been downloaded, or are still only pointer files
+pub fn lfs_status(worktree_path: &str) -> Result<Vec<LfsFile>, String> {
+    let output = run_git(worktree_path, &["lfs", "ls-files", "--long"]).map_err(|e| {
+        if e.contains("not a git command") {
+            "Git LFS is not installed".to_string()
+        } else {
+            e
+        }
+    })?;
+    Ok(parse_lfs_ls_files(&output))
+}
+
+/// Parse `git lfs ls-files --long` lines: "<oid> <*|-> <path>", where * means
+/// the object is present and - that only the pointer is checked out
+fn parse_lfs_ls_files(output: &str) -> Vec<LfsFile> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (oid, rest) = line.split_once(' ')?;
+            let (marker, path) = rest.split_once(' ')?;
+            Some(LfsFile {
+                path: path.to_string(),
+                oid: oid.to_string(),
+                downloaded: marker == "*",
+            })
+        })
+        .collect()
+}
+
+/// Parse `git check-attr -z filter` output (path NUL attr NUL value NUL ...)
+/// into the paths whose filter is lfs
+fn parse_lfs_check_attr(output: &str) -> HashSet<String> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks_exact(3)
+        .filter(|entry| entry[2] == "lfs")
+        .map(|entry| entry[0].to_string())
+        .collect()
+}
+
+/// Drop the hunks of files that don't fit in the remaining byte budget,
+/// marking them truncated. A file too large to fit doesn't use up the budget
+/// for the smaller ones after it. Returns the budget left over.
+fn truncate_large_diffs(files: &mut [FileDiff], mut budget: u64) -> u64 {
+    for file in files {
+        let size: u64 = file
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .map(|line| line.content.len() as u64 + 1)
+            .sum();
+        if size > budget {
+            file.hunks.clear();
+            file.truncated = true;
+        } else {
+            budget -= size;
+        }
+    }
+    budget
+}
+
+/// Get the changes saved in the stash entry `stash@{stash_index}`, optionally
+/// including the untracked files it saved
+pub fn get_stash_diff(
+    repo_path: &str,
+    stash_index: usize,
+    include_untracked: bool,
+) -> Result<CommitDiff, String> {
+    let stash_ref = format!("stash@{{{}}}", stash_index);
+    if run_git(repo_path, &["rev-parse", "-q", "--verify", &stash_ref]).is_err() {
+        return Err(format!("No stash entry at index {}", stash_index));
+    }
+
+    let commit_info = get_commit_info(repo_path, &stash_ref)?;
+
+    let mut args = vec!["stash", "show", "-p", "-U3", "-M"];
+    if include_untracked {
+        args.push("--include-untracked");
+    }
+    args.push(&stash_ref);
+    let mut files = parse_git_diff_output(&run_git(repo_path, &args)?);
+    let stats = sum_diff_stats(&files);
+    mark_lfs_files(repo_path, &mut files);
+
+    Ok(CommitDiff {
+        commit: commit_info,
+        files,
+        stats,
+    })
+}
+
+/// Get a single commit's metadata
+fn get_commit_info(worktree_path: &str, commit_sha: &str) -> Result<CommitInfo, String> {
+    let format = "%H%x1f%h%x1f%an%x1f%ae%x1f%ct%x1f%s%x1f%B";
+    let commit_output = run_git(
+        worktree_path,
+        &["log", "-1", &format!("--format={}", format), commit_sha],
+    )?;
+
+    let fields: Vec<&str> = commit_output.trim().split('\x1f').collect();
+    if fields.len() < 6 {
+        return Err(format!("Failed to parse commit info for {}", commit_sha));
+    }
+
+    Ok(CommitInfo {
+        hash: fields[0].to_string(),
+        short_hash: fields[1].to_string(),
+        author_name: fields[2].to_string(),
+        author_email: fields[3].to_string(),
+        commit_timestamp: fields[4].parse::<i64>().unwrap_or(0),
+        summary: fields[5].to_string(),
+        message: fields.get(6).unwrap_or(&"").trim().to_string(),
+    })
+}
+
+/// Count changed files and added/removed lines in parsed diffs
+fn sum_diff_stats(files: &[FileDiff]) -> DiffStats {
+    let mut total_insertions = 0u32;
+    let mut total_deletions = 0u32;
+
+    for file in files {
+        for hunk in &file.hunks {
+            for line in &hunk.lines {
+                match line.kind {
+                    '+' => total_insertions += 1,
+                    '-' => total_deletions += 1,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    DiffStats {
+        files_changed: files.len() as u32,
+        insertions: total_insertions,
+        deletions: total_deletions,
+    }
+}
+
+/// Check if content is binary by looking for null bytes in the first 8KB
+fn is_binary_content(content: &[u8]) -> bool {
+    let check_len = content.len().min(8192);
+    content[..check_len].contains(&0)
+}
+
+/// Get a file's contents as of a commit
+pub fn get_file_at_commit(
+    worktree_path: &str,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<FileBlob, String> {
+    let commit = verify_commit(worktree_path, commit_sha)?;
+    let spec = format!("{}:{}", commit, file_path);
+    let output = Command::new("git")
+        .args(["-C", worktree_path, "show", &spec])
+        .output()
+        .map_err(|e| format!("Failed to run git show: {}", e))?;
+
+    // Fails for paths that didn't exist at that commit
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git show {} failed: {}", spec, stderr.trim()));
+    }
+
+    if is_binary_content(&output.stdout) {
+        return Ok(FileBlob {
+            content: None,
+            is_binary: true,
+        });
+    }
+
+    // Non-UTF8 treated as binary
+    Ok(match String::from_utf8(output.stdout) {
+        Ok(content) => FileBlob {
+            content: Some(content),
+            is_binary: false,
+        },
+        Err(_) => FileBlob {
+            content: None,
+            is_binary: true,
+        },
+    })
+}
+
+/// Resolve a caller-supplied revision to a full commit SHA. Checking it this
+/// way keeps values such as "--output=x" from being parsed as options when
+/// they're passed on to other git commands.
+fn verify_commit(worktree_path: &str, rev: &str) -> Result<String, String> {
+    let spec = format!("{}^{{commit}}", rev);
+    run_git(
+        worktree_path,
+        &["rev-parse", "-q", "--verify", "--end-of-options", &spec],
+    )
+    .map(|sha| sha.trim().to_string())
+    .map_err(|_| format!("Not a commit: {}", rev))
+}
+
+/// List the tracked and untracked (but not ignored) files and directories
+/// directly inside `subpath`, or the worktree root. Directories come first.
+pub fn list_tree(worktree_path: &str, subpath: Option<&str>) -> Result<Vec<TreeEntry>, String> {
+    let prefix = subpath.unwrap_or("").trim_matches('/');
+    // Tracked (-c) and untracked (-o) files, NUL-separated so names aren't quoted
+    let mut args = vec!["ls-files", "-z", "-c", "-o", "--exclude-standard"];
+    let pathspec = format!(":(literal){}", prefix);
+    if !prefix.is_empty() {
+        args.extend(["--", &pathspec]);
+    }
+    let output = run_git(worktree_path, &args)?;
+    Ok(tree_children(output.split('\0'), prefix))
+}
+
+/// Collapse full file paths into the direct children of the `prefix` directory
+fn tree_children<'a>(paths: impl Iterator<Item = &'a str>, prefix: &str) -> Vec<TreeEntry> {
+    let mut entries: Vec<TreeEntry> = Vec::new();
+
+    for path in paths {
+        let relative = if prefix.is_empty() {
+            path
+        } else {
+            match path
+                .strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => continue,
+            }
+        };
+        if relative.is_empty() {
+            continue;
+        }
+
+        let (name, is_dir) = match relative.split_once('/') {
+            Some((dir, _)) => (dir, true),
+            None => (relative, false),
+        };
+        let path = if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        // A directory's files are mostly listed together, so this skips most
+        // repeats early. Untracked files come after tracked ones, though, and
+        // conflicted files once per stage, so dedup again after sorting.
+        if entries.last().map(|e| &e.path) != Some(&path) {
+            entries.push(TreeEntry { path, is_dir });
+        }
+    }
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    entries.dedup();
+    entries
+}
+
+/// Get file/line totals for the changes between two revisions, or between
+/// `from` and the working tree when `to` is None. Much cheaper than a full diff.
+pub fn diff_summary(
+    worktree_path: &str,
+    from: &str,
+    to: Option<&str>,
+) -> Result<DiffStats, String> {
+    // Both ends come from the caller, so they mustn't be read as options
+    let from = verify_commit(worktree_path, from)?;
+    let range = match to {
+        Some(to) => format!("{}..{}", from, verify_commit(worktree_path, to)?),
+        None => from,
+    };
+    let output = run_git(worktree_path, &["diff", "--shortstat", &range, "--"])?;
+    Ok(parse_shortstat(&output))
+}
+
+/// Apply a patch to a worktree with `git apply`, or only check whether it
+/// would apply. Hunks that don't match are reported as conflicts.
+pub fn apply_patch(
+    worktree_path: &str,
+    patch_text: String,
+    check_only: bool,
+) -> Result<ApplyResult, String> {
+    let mut args = vec!["apply", "--verbose"];
+    if check_only {
+        args.push("--check");
+    }
+
+    let output = git_output_with_input(worktree_path, &args, patch_text.into_bytes())?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Exit code 1 means some hunks didn't apply; anything else is an unreadable patch
+    if !matches!(output.status.code(), Some(0) | Some(1)) {
+        return Err(format!("git apply failed: {}", stderr));
+    }
+
+    let (clean_files, conflicts) = parse_apply_output(&stderr);
+    Ok(ApplyResult {
+        applied: !check_only && output.status.success(),
+        clean_files,
+        conflicts,
+    })
+}
+
+/// Export uncommitted changes to tracked files as a patch that `git apply`
+/// accepts, binary files included. Untracked files aren't part of the diff.
+pub fn export_patch(worktree_path: &str, staged_only: bool) -> Result<String, String> {
+    if staged_only {
+        return run_git(worktree_path, &["diff", "--binary", "--cached", "--"]);
+    }
+
+    // Both staged and unstaged changes. Before the first commit there's no HEAD
+    // to diff against, so everything is compared with the empty tree instead.
+    let base = match verify_commit(worktree_path, "HEAD") {
+        Ok(head) => head,
+        Err(_) => empty_tree(worktree_path)?,
+    };
+    run_git(worktree_path, &["diff", "--binary", &base, "--"])
+}
+
+/// The id of the empty tree in this repo's object format
+fn empty_tree(worktree_path: &str) -> Result<String, String> {
+    let output = run_git_with_input(
+        worktree_path,
+        &["hash-object", "-t", "tree", "--stdin"],
+        Vec::new(),
+    )?;
+    Ok(output.trim().to_string())
+}
+
+/// Generate synthetic diff hunks for a new/untracked file
+/// Returns (hunks, is_binary) - empty hunks if binary or read fails
+fn generate_new_file_hunks(file_path: &Path) -> (Vec<DiffHunk>, bool) {
+    // Read file content
+    let content = match fs::read(file_path) {
+        Ok(bytes) => bytes,
+        Err(_) => return (Vec::new(), false),
+    };
+
+    if is_binary_content(&content) {
+        return (Vec::new(), true);
+    }
+
+    // Convert to string
+    let text = match String::from_utf8(content) {
+        Ok(s) => s,
+        Err(_) => return (Vec::new(), true), // Non-UTF8 treated as binary
+    };
+
+    // Split into lines
+    let lines: Vec<&str> = text.lines().collect();
+    let line_count = lines.len() as u32;
+
+    if line_count == 0 {
+        return (Vec::new(), false);
+    }
+
+    // Create diff lines (all additions)
+    let diff_lines: Vec<DiffLine> = lines
+        .into_iter()
+        .map(|line| DiffLine {
+            kind: '+',
+            content: line.to_string(),
+        })
+        .collect();
+
+    // Create single hunk for the entire file
+    let hunk = DiffHunk {
+        old_start: 0,
+        old_lines: 0,
+        new_start: 1,
+        new_lines: line_count,
+        header: format!("@@ -0,0 +1,{} @@", line_count),
+        lines: diff_lines,
+    };
+
+    (vec![hunk], false)
+}
+
+/// Get staged and unstaged changes. With `include_untracked`, untracked files
+/// are listed as unstaged additions of their whole contents. Hunks beyond
+/// `max_diff_bytes` are left out, staged files first.
+pub fn get_working_diff(
+    worktree_path: &str,
+    include_untracked: bool,
+    max_diff_bytes: u64,
+    ignore_whitespace: bool,
+) -> Result<WorkingDiff, String> {
+    let mut args = vec!["diff", "-U3"];
+    if ignore_whitespace {
+        args.push("--ignore-all-space");
+    }
+
+    // Get staged changes: git diff --cached
+    let mut staged_args = args.clone();
+    staged_args.push("--cached");
+    let staged_diff_text = run_git(worktree_path, &staged_args)?;
+    let mut staged_files = parse_git_diff_output(&staged_diff_text);
+
+    // Get unstaged changes: git diff
+    let unstaged_diff_text = run_git(worktree_path, &args)?;
+    let mut unstaged_files = parse_git_diff_output(&unstaged_diff_text);
+
+    // Get untracked files: git ls-files --others --exclude-standard
+    if include_untracked {
+        let untracked_text = run_git(
+            worktree_path,
+            &["ls-files", "--others", "--exclude-standard"],
+        )?;
+        let worktree_dir = Path::new(worktree_path);
+        for line in untracked_text.lines() {
+            if !line.is_empty() {
+                let file_path = worktree_dir.join(line);
+                // Don't read files that couldn't fit anyway
+                let truncated = fs::metadata(&file_path).is_ok_and(|m| m.len() > max_diff_bytes);
+                let (hunks, binary) = if truncated {
+                    (Vec::new(), false)
+                } else {
+                    generate_new_file_hunks(&file_path)
+                };
+                unstaged_files.push(FileDiff {
+                    path: line.to_string(),
+                    status: FileStatus::Added,
+                    old_path: None,
+                    hunks,
+                    binary,
+                    truncated,
+                    is_lfs: false,
+                });
+            }
+        }
+    }
+
+    // Calculate total stats
+    let mut total_insertions = 0u32;
+    let mut total_deletions = 0u32;
 
     for file in staged_files.iter().chain(unstaged_files.iter()) {
         for hunk in &file.hunks {
@@ -346,6 +1411,11 @@ pub fn get_working_diff(worktree_path: &str) -> Result<WorkingDiff, String> {
 
     let files_changed = (staged_files.len() + unstaged_files.len()) as u32;
 
+    mark_lfs_files(worktree_path, &mut staged_files);
+    mark_lfs_files(worktree_path, &mut unstaged_files);
+    let budget = truncate_large_diffs(&mut staged_files, max_diff_bytes);
+    truncate_large_diffs(&mut unstaged_files, budget);
+
     Ok(WorkingDiff {
         staged_files,
         unstaged_files,
@@ -393,6 +1463,8 @@ fn parse_git_diff_output(diff_text: &str) -> Vec<FileDiff> {
                 old_path: None,
                 hunks: Vec::new(),
                 binary: false,
+                truncated: false,
+                is_lfs: false,
             });
             continue;
         }
@@ -522,47 +1594,771 @@ fn parse_range(s: &str) -> Option<(u32, u32)> {
     Some((start, lines))
 }
 
-/// Create a new worktree
-pub fn create_worktree(repo_path: &str, options: CreateWorktreeOptions) -> Result<Worktree, String> {
-    let mut args = vec!["worktree", "add"];
-
-    // Build temporary strings to hold the branch flag
-    let branch_flag;
-    if let Some(ref branch) = options.new_branch {
-        branch_flag = format!("-b");
-        args.push(&branch_flag);
-        args.push(branch);
+/// Detect an in-progress operation from the state files git leaves in the git dir
+fn operation_from_git_dir(git_dir: &Path) -> Option<GitOperation> {
+    if git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists() {
+        Some(GitOperation::Rebase)
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Some(GitOperation::Merge)
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        Some(GitOperation::CherryPick)
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        Some(GitOperation::Revert)
+    } else {
+        None
     }
+}
 
-    if options.detach {
-        args.push("--detach");
-    }
+/// Read progress for an in-progress operation. Only merge-backend rebases
+/// record their step (rebase-merge/msgnum and rebase-merge/end).
+fn operation_progress_from_git_dir(git_dir: &Path) -> Option<OperationProgress> {
+    let operation = operation_from_git_dir(git_dir)?;
 
-    args.push(&options.path);
+    let read_count = |name: &str| -> Option<u32> {
+        fs::read_to_string(git_dir.join("rebase-merge").join(name))
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+    };
 
-    if let Some(ref commit_ish) = options.commit_ish {
-        args.push(commit_ish);
+    let (current, total) = if operation == GitOperation::Rebase {
+        (read_count("msgnum"), read_count("end"))
+    } else {
+        (None, None)
+    };
+
+    Some(OperationProgress {
+        operation,
+        current,
+        total,
+    })
+}
+
+/// Resolve a worktree's own gitdir (`<common>/worktrees/<name>` for linked
+/// worktrees) and the gitdir shared by all worktrees of its repository
+pub fn gitdir_for_worktree(worktree_path: &str) -> Result<GitDirs, String> {
+    let output = run_git(
+        worktree_path,
+        &[
+            "rev-parse",
+            "--path-format=absolute",
+            "--git-dir",
+            "--git-common-dir",
+        ],
+    )?;
+    let mut lines = output.lines().map(str::trim);
+    let (Some(git_dir), Some(common_dir)) = (lines.next(), lines.next()) else {
+        return Err(format!("Failed to resolve git dirs for {}", worktree_path));
+    };
+
+    Ok(GitDirs {
+        git_dir: PathBuf::from(git_dir),
+        common_dir: PathBuf::from(common_dir),
+    })
+}
+
+/// The git subcommand that drives an operation
+fn operation_command(operation: GitOperation) -> &'static str {
+    match operation {
+        GitOperation::Rebase => "rebase",
+        GitOperation::Merge => "merge",
+        GitOperation::CherryPick => "cherry-pick",
+        GitOperation::Revert => "revert",
     }
+}
 
-    run_git(repo_path, &args)?;
+/// Get the rebase/merge/cherry-pick/revert in progress in a worktree, if any
+pub fn detect_operation(worktree_path: &str) -> Result<Option<GitOperation>, String> {
+    // Each worktree has its own git dir, so this doesn't see other worktrees' operations
+    let git_dir = gitdir_for_worktree(worktree_path)?.git_dir;
+    Ok(operation_from_git_dir(&git_dir))
+}
 
-    // Build and return the new worktree info
-    let path = PathBuf::from(&options.path);
-    build_worktree_info(&path, false)
+/// Check whether a worktree can be switched, reset or deleted without losing
+/// work: no staged, unstaged or untracked changes and no operation in progress
+pub fn is_worktree_clean(worktree_path: &str) -> Result<CleanState, String> {
+    let counts = count_changes(worktree_path)?;
+    let operation = detect_operation(worktree_path)?;
+    Ok(clean_state(&counts, operation))
 }
 
-/// Delete a worktree
-pub fn delete_worktree(repo_path: &str, worktree_path: &str, force: bool) -> Result<(), String> {
-    let mut args = vec!["worktree", "remove"];
+fn clean_state(counts: &ChangeCounts, operation: Option<GitOperation>) -> CleanState {
+    let mut reasons = Vec::new();
+    if counts.staged > 0 {
+        reasons.push(format!("{} staged change(s)", counts.staged));
+    }
+    if counts.unstaged > 0 {
+        reasons.push(format!("{} unstaged change(s)", counts.unstaged));
+    }
+    if counts.untracked > 0 {
+        reasons.push(format!("{} untracked file(s)", counts.untracked));
+    }
+    if let Some(operation) = operation {
+        reasons.push(format!("{} in progress", operation_command(operation)));
+    }
 
-    if force {
-        args.push("--force");
+    CleanState {
+        clean: reasons.is_empty(),
+        reasons,
     }
+}
 
-    args.push(worktree_path);
+/// List files with unresolved conflicts
+fn get_conflicted_files(worktree_path: &str) -> Result<Vec<String>, String> {
+    let output = run_git(worktree_path, &["diff", "--name-only", "--diff-filter=U"])?;
+    Ok(output.lines().map(|l| l.to_string()).collect())
+}
 
-    run_git(repo_path, &args)?;
-    Ok(())
+/// Count the unmerged files of the operation in progress, or None when there
+/// is no operation
+pub fn operation_conflict_count(worktree_path: &str) -> Result<Option<u32>, String> {
+    if detect_operation(worktree_path)?.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(get_conflicted_files(worktree_path)?.len() as u32))
+}
+
+/// List conflicted files with the conflict hunks parsed from their markers
+pub fn get_conflicts(worktree_path: &str) -> Result<Vec<ConflictFile>, String> {
+    let files = get_conflicted_files(worktree_path)?
+        .into_iter()
+        .map(|path| {
+            let hunks = fs::read_to_string(Path::new(worktree_path).join(&path))
+                .map(|content| parse_conflict_hunks(&content))
+                .unwrap_or_default();
+            ConflictFile { path, hunks }
+        })
+        .collect();
+    Ok(files)
+}
+
+/// Parse conflict hunks from file contents, in both the standard (merge) and
+/// diff3 marker styles. An unterminated hunk is ignored.
+fn parse_conflict_hunks(content: &str) -> Vec<ConflictHunk> {
+    enum Section {
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    /// The label after a conflict marker, if the line is that marker
+    fn marker_label<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+        let rest = line.trim_end_matches(['\r', '\n']).strip_prefix(marker)?;
+        if rest.is_empty() {
+            Some("")
+        } else {
+            rest.strip_prefix(' ')
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<(ConflictHunk, Section)> = None;
+
+    for (index, line) in content.split_inclusive('\n').enumerate() {
+        let line_number = index as u32 + 1;
+
+        if let Some(label) = marker_label(line, "<<<<<<<") {
+            // Also restarts a hunk that was never closed
+            let hunk = ConflictHunk {
+                start_line: line_number,
+                end_line: line_number,
+                ours_label: label.to_string(),
+                theirs_label: String::new(),
+                ours: String::new(),
+                base: None,
+                theirs: String::new(),
+            };
+            current = Some((hunk, Section::Ours));
+            continue;
+        }
+
+        let Some((hunk, section)) = current.as_mut() else {
+            continue;
+        };
+        match section {
+            Section::Ours if marker_label(line, "|||||||").is_some() => {
+                hunk.base = Some(String::new());
+                *section = Section::Base;
+            }
+            Section::Ours | Section::Base if marker_label(line, "=======") == Some("") => {
+                *section = Section::Theirs;
+            }
+            Section::Ours => hunk.ours.push_str(line),
+            Section::Base => hunk.base.get_or_insert_with(String::new).push_str(line),
+            Section::Theirs => match marker_label(line, ">>>>>>>") {
+                Some(label) => {
+                    hunk.end_line = line_number;
+                    hunk.theirs_label = label.to_string();
+                    hunks.extend(current.take().map(|(hunk, _)| hunk));
+                }
+                None => hunk.theirs.push_str(line),
+            },
+        }
+    }
+
+    hunks
+}
+
+/// Whether file contents still contain git conflict markers
+fn has_conflict_markers(content: &str) -> bool {
+    let mut in_conflict = false;
+    for line in content.lines() {
+        if line.starts_with("<<<<<<< ") {
+            in_conflict = true;
+        } else if in_conflict && line.starts_with(">>>>>>> ") {
+            return true;
+        }
+    }
+    false
+}
+
+/// Mark conflicted files as resolved (`git add`). Fails without staging anything
+/// if a path isn't conflicted or still contains conflict markers.
+pub fn mark_resolved(worktree_path: &str, paths: &[String]) -> Result<(), String> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let conflicted = get_conflicted_files(worktree_path)?;
+    let not_conflicted: Vec<&str> = paths
+        .iter()
+        .filter(|p| !conflicted.contains(p))
+        .map(|p| p.as_str())
+        .collect();
+    if !not_conflicted.is_empty() {
+        return Err(format!("Not conflicted: {}", not_conflicted.join(", ")));
+    }
+
+    // Deleted-by-us/them conflicts have no file to check; staging resolves them
+    let unresolved: Vec<&str> = paths
+        .iter()
+        .filter(|p| {
+            fs::read_to_string(Path::new(worktree_path).join(p))
+                .map(|content| has_conflict_markers(&content))
+                .unwrap_or(false)
+        })
+        .map(|p| p.as_str())
+        .collect();
+    if !unresolved.is_empty() {
+        return Err(format!(
+            "Still contains conflict markers: {}",
+            unresolved.join(", ")
+        ));
+    }
+
+    let mut args = vec!["add", "--"];
+    args.extend(paths.iter().map(|p| p.as_str()));
+    run_git(worktree_path, &args)?;
+
+    Ok(())
+}
+
+/// Write the merged contents of a conflicted file and mark it resolved.
+/// Returns how many conflicted files remain.
+pub fn resolve_conflict(
+    worktree_path: &str,
+    file_path: &str,
+    resolved_content: String,
+) -> Result<u32, String> {
+    if !get_conflicted_files(worktree_path)?
+        .iter()
+        .any(|p| p == file_path)
+    {
+        return Err(format!("Not conflicted: {}", file_path));
+    }
+    if has_conflict_markers(&resolved_content) {
+        return Err(format!("Still contains conflict markers: {}", file_path));
+    }
+
+    fs::write(Path::new(worktree_path).join(file_path), resolved_content)
+        .map_err(|e| format!("Failed to write {}: {}", file_path, e))?;
+    mark_resolved(worktree_path, &[file_path.to_string()])?;
+
+    Ok(get_conflicted_files(worktree_path)?.len() as u32)
+}
+
+/// Run `--continue` for the operation in progress. Returns the resulting state:
+/// no operation once it has completed, or the operation and its conflicted
+/// files if it stopped at the next conflict.
+pub fn continue_operation(worktree_path: &str) -> Result<OperationState, String> {
+    let operation = detect_operation(worktree_path)?.ok_or("No operation in progress")?;
+
+    let command = operation_command(operation);
+
+    // Accept the prepared commit messages instead of opening an editor
+    let output = Command::new("git")
+        .args(["-C", worktree_path, command, "--continue"])
+        .env("GIT_EDITOR", "true")
+        .output()
+        .map_err(|e| format!("Failed to run git {} --continue: {}", command, e))?;
+
+    let state = OperationState {
+        operation: detect_operation(worktree_path)?,
+        conflicted_files: get_conflicted_files(worktree_path)?,
+    };
+
+    // A failure that didn't leave conflicts behind (e.g. unresolved files) is a real error
+    if !output.status.success() && state.conflicted_files.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Err(format!(
+            "git {} --continue failed: {}{}",
+            command, stdout, stderr
+        ));
+    }
+
+    Ok(state)
+}
+
+/// Get the commit the stash ref points at, or None if there are no stashes
+fn get_stash_head(worktree_path: &str) -> Option<String> {
+    run_git(
+        worktree_path,
+        &["rev-parse", "-q", "--verify", "refs/stash"],
+    )
+    .ok()
+    .map(|s| s.trim().to_string())
+}
+
+/// Stash changes, optionally limited to `paths`. Returns the new stash's index,
+/// which is always 0 since git pushes new entries onto the top of the stack.
+pub fn stash_save(
+    worktree_path: &str,
+    message: Option<&str>,
+    keep_index: bool,
+    paths: Option<&[String]>,
+) -> Result<usize, String> {
+    let mut args = vec!["stash", "push"];
+    if keep_index {
+        args.push("--keep-index");
+    }
+    if let Some(message) = message {
+        args.extend(["-m", message]);
+    }
+    if let Some(paths) = paths {
+        args.push("--");
+        args.extend(paths.iter().map(|p| p.as_str()));
+    }
+
+    // git exits successfully without creating a stash when there's nothing to save
+    let before = get_stash_head(worktree_path);
+    run_git(worktree_path, &args)?;
+    if get_stash_head(worktree_path) == before {
+        return Err("No local changes to stash".to_string());
+    }
+
+    Ok(0)
+}
+
+/// Check whether a path can be used for a new worktree before creating it.
+/// Missing intermediate directories are fine (git creates them), but the
+/// nearest existing ancestor must be a writable directory.
+pub fn validate_worktree_target(repo_path: &str, path: &str) -> Result<ValidationResult, String> {
+    let invalid = |issue: WorktreeTargetIssue, message: String| ValidationResult {
+        valid: false,
+        issue: Some(issue),
+        message: Some(message),
+    };
+
+    // Relative paths are resolved by git against the repo directory
+    let target = Path::new(repo_path).join(path);
+    let target = target.canonicalize().unwrap_or(target);
+
+    // A registered worktree whose folder was deleted still blocks `worktree add`
+    let output = run_git(repo_path, &["worktree", "list", "--porcelain"])?;
+    for line in output.lines() {
+        if let Some(existing) = line.strip_prefix("worktree ") {
+            let existing = Path::new(existing);
+            let existing = existing
+                .canonicalize()
+                .unwrap_or_else(|_| existing.to_path_buf());
+            if existing == target {
+                return Ok(invalid(
+                    WorktreeTargetIssue::AlreadyWorktree,
+                    format!("{} is already a worktree", target.display()),
+                ));
+            }
+        }
+    }
+
+    if target.exists() {
+        if !target.is_dir() {
+            return Ok(invalid(
+                WorktreeTargetIssue::NotADirectory,
+                format!("{} exists and is not a directory", target.display()),
+            ));
+        }
+        let is_empty = fs::read_dir(&target)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if !is_empty {
+            return Ok(invalid(
+                WorktreeTargetIssue::PathNotEmpty,
+                format!("{} already exists and is not empty", target.display()),
+            ));
+        }
+    }
+
+    let Some(ancestor) = target.ancestors().skip(1).find(|p| p.exists()) else {
+        return Ok(invalid(
+            WorktreeTargetIssue::ParentMissing,
+            format!("No parent directory of {} exists", target.display()),
+        ));
+    };
+
+    if !ancestor.is_dir() {
+        return Ok(invalid(
+            WorktreeTargetIssue::ParentMissing,
+            format!("{} is not a directory", ancestor.display()),
+        ));
+    }
+
+    if !is_writable_dir(ancestor) {
+        return Ok(invalid(
+            WorktreeTargetIssue::ParentNotWritable,
+            format!("{} is not writable", ancestor.display()),
+        ));
+    }
+
+    Ok(ValidationResult {
+        valid: true,
+        issue: None,
+        message: None,
+    })
+}
+
+/// Get the repository's name: the directory containing the common .git dir,
+/// or the bare repository directory itself (without a .git suffix)
+fn get_repo_name(repo_path: &str) -> Result<String, String> {
+    let common_dir = gitdir_for_worktree(repo_path)?.common_dir;
+    let common_dir = common_dir.as_path();
+
+    let repo_dir = if common_dir.file_name().is_some_and(|n| n == ".git") {
+        common_dir.parent().unwrap_or(common_dir)
+    } else {
+        common_dir
+    };
+
+    repo_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().trim_end_matches(".git").to_string())
+        .ok_or_else(|| format!("Could not determine repository name for {}", repo_path))
+}
+
+/// Build <base>/<repo-name>/<branch>, flattening slashes in the branch name
+/// so "feature/login" becomes a single "feature-login" directory
+fn derive_worktree_path(base_dir: &str, repo_name: &str, branch: &str) -> PathBuf {
+    Path::new(base_dir)
+        .join(repo_name)
+        .join(branch.replace('/', "-"))
+}
+
+/// Resolve the target path for a new worktree, deriving it from base_dir when
+/// no explicit path was given
+fn resolve_worktree_path(
+    repo_path: &str,
+    options: &CreateWorktreeOptions,
+    base_dir: Option<&str>,
+) -> Result<String, String> {
+    if let Some(ref path) = options.path {
+        return Ok(path.clone());
+    }
+
+    let base_dir = base_dir.ok_or("No worktree path given and no worktree_base_dir configured")?;
+    let branch = options
+        .new_branch
+        .as_ref()
+        .or(options.commit_ish.as_ref())
+        .ok_or("A branch name is required to derive the worktree path")?;
+
+    let repo_name = get_repo_name(repo_path)?;
+    Ok(derive_worktree_path(base_dir, &repo_name, branch)
+        .to_string_lossy()
+        .to_string())
+}
+
+/// Whether the current user can create entries in `dir`. Permission bits
+/// alone don't tell (ownership, ACLs, read-only mounts), so try it.
+fn is_writable_dir(dir: &Path) -> bool {
+    let probe = dir.join(format!(".woodeye-write-test-{}", std::process::id()));
+    match fs::create_dir(&probe) {
+        Ok(()) => {
+            let _ = fs::remove_dir(&probe);
+            true
+        }
+        Err(e) => e.kind() == std::io::ErrorKind::AlreadyExists,
+    }
+}
+
+/// Create a new worktree
+pub fn create_worktree(
+    repo_path: &str,
+    options: CreateWorktreeOptions,
+    base_dir: Option<&str>,
+) -> Result<Worktree, String> {
+    let target_path = resolve_worktree_path(repo_path, &options, base_dir)?;
+
+    // Validate up front so a bad target never leaves a half-created worktree
+    let validation = validate_worktree_target(repo_path, &target_path)?;
+    if !validation.valid {
+        return Err(validation
+            .message
+            .unwrap_or_else(|| "Invalid worktree path".to_string()));
+    }
+
+    let mut args = vec!["worktree", "add"];
+
+    // Build temporary strings to hold the branch flag
+    let branch_flag;
+    if let Some(ref branch) = options.new_branch {
+        branch_flag = format!("-b");
+        args.push(&branch_flag);
+        args.push(branch);
+    }
+
+    if options.detach {
+        args.push("--detach");
+    }
+
+    args.push(&target_path);
+
+    if let Some(ref commit_ish) = options.commit_ish {
+        args.push(commit_ish);
+    }
+
+    run_git(repo_path, &args)?;
+
+    // Build and return the new worktree info
+    get_worktree(repo_path, &target_path)
+}
+
+/// Delete a worktree
+pub fn delete_worktree(
+    repo_path: &str,
+    worktree_path: &str,
+    force: bool,
+    delete_branch: bool,
+) -> Result<DeleteWorktreeResult, String> {
+    // Read the branch while the worktree's HEAD still exists. If the directory
+    // is already gone, only the repository's record of it is left.
+    let branch = if !delete_branch {
+        None
+    } else if Path::new(worktree_path).exists() {
+        run_git(worktree_path, &["symbolic-ref", "--short", "-q", "HEAD"])
+            .ok()
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty())
+    } else {
+        list_worktree_records(repo_path)?
+            .into_iter()
+            .find(|r| r.path == Path::new(worktree_path))
+            .and_then(|r| r.branch)
+    };
+
+    let mut args = vec!["worktree", "remove"];
+
+    if force {
+        args.push("--force");
+    }
+
+    args.push(worktree_path);
+
+    run_git(repo_path, &args)?;
+
+    let mut result = DeleteWorktreeResult {
+        deleted_branch: None,
+        kept_branch_reason: None,
+    };
+    let Some(branch) = branch else {
+        return Ok(result);
+    };
+
+    if !force {
+        let unpushed = count_unpushed_commits(repo_path, &branch)?;
+        if unpushed > 0 {
+            result.kept_branch_reason = Some(format!(
+                "{} has {} commit(s) not on any remote or other branch",
+                branch, unpushed
+            ));
+            return Ok(result);
+        }
+    }
+
+    // Nothing would be lost (or the caller forced it), so -D is safe even when
+    // the branch isn't merged into HEAD
+    match run_git(repo_path, &["branch", "-D", "--", &branch]) {
+        Ok(_) => result.deleted_branch = Some(branch),
+        Err(e) => result.kept_branch_reason = Some(e),
+    }
+    Ok(result)
+}
+
+/// Delete several worktrees one after another, continuing past failures
+pub fn delete_worktrees(
+    repo_path: &str,
+    worktree_paths: &[String],
+    force: bool,
+    delete_branch: bool,
+) -> Vec<DeleteOutcome> {
+    worktree_paths
+        .iter()
+        .map(
+            |path| match delete_worktree(repo_path, path, force, delete_branch) {
+                Ok(result) => DeleteOutcome {
+                    path: path.clone(),
+                    result: Some(result),
+                    error: None,
+                },
+                Err(e) => DeleteOutcome {
+                    path: path.clone(),
+                    result: None,
+                    error: Some(e),
+                },
+            },
+        )
+        .collect()
+}
+
+/// Parse a layout written by export_worktrees, rejecting anything that
+/// couldn't be recreated as a whole
+fn parse_worktree_layout(layout_json: &str) -> Result<WorktreeExport, String> {
+    let layout: WorktreeExport =
+        serde_json::from_str(layout_json).map_err(|e| format!("Not a worktree layout: {}", e))?;
+    for worktree in &layout.worktrees {
+        if !worktree.path.is_absolute() {
+            return Err(format!(
+                "Not a worktree layout: {} is not an absolute path",
+                worktree.path.display()
+            ));
+        }
+        if worktree.head.branch.is_none() && worktree.head.commit_sha.is_empty() {
+            return Err(format!(
+                "Not a worktree layout: {} has neither a branch nor a commit",
+                worktree.path.display()
+            ));
+        }
+    }
+    Ok(layout)
+}
+
+/// Recreate the worktrees of an exported layout that don't exist yet. Branches
+/// missing locally are created from a remote (fetching once if needed), or
+/// else at the exported commit; detached worktrees are checked out at it.
+pub fn import_worktrees(repo_path: &str, layout_json: &str) -> Result<Vec<CreateOutcome>, String> {
+    let layout = parse_worktree_layout(layout_json)?;
+    let existing = list_worktree_records(repo_path)?;
+    let mut fetched = false;
+
+    let mut outcomes = Vec::new();
+    for exported in layout.worktrees.iter().filter(|w| !w.is_main && !w.is_bare) {
+        let path = exported.path.to_string_lossy().to_string();
+        let branch = exported.head.branch.clone();
+        let present = existing
+            .iter()
+            .any(|r| r.path == exported.path && r.prunable.is_none());
+
+        let result = if present {
+            Ok(None)
+        } else {
+            import_options(repo_path, exported, &mut fetched)
+                .and_then(|options| create_worktree(repo_path, options, None))
+                .map(Some)
+        };
+
+        outcomes.push(match result {
+            Ok(worktree) => CreateOutcome {
+                path,
+                branch,
+                skipped: worktree.is_none(),
+                worktree,
+                error: None,
+            },
+            Err(e) => CreateOutcome {
+                path,
+                branch,
+                worktree: None,
+                skipped: false,
+                error: Some(e),
+            },
+        });
+    }
+    Ok(outcomes)
+}
+
+/// How to recreate an exported worktree in this repository
+fn import_options(
+    repo_path: &str,
+    exported: &Worktree,
+    fetched: &mut bool,
+) -> Result<CreateWorktreeOptions, String> {
+    let path = Some(exported.path.to_string_lossy().to_string());
+    let commit = exported.head.commit_sha.clone();
+    let Some(branch) = exported.head.branch.clone() else {
+        return Ok(CreateWorktreeOptions {
+            path,
+            new_branch: None,
+            commit_ish: Some(commit),
+            detach: true,
+        });
+    };
+
+    let mut existence = branch_exists(repo_path, &branch, false)?;
+    if !existence.local && existence.remote.is_none() && !*fetched {
+        run_git(repo_path, &["fetch", "--all"])?;
+        *fetched = true;
+        existence = branch_exists(repo_path, &branch, false)?;
+    }
+
+    if existence.local {
+        return Ok(CreateWorktreeOptions {
+            path,
+            new_branch: None,
+            commit_ish: Some(branch),
+            detach: false,
+        });
+    }
+    let commit_object = format!("{}^{{commit}}", commit);
+    let commit_exists = || run_git(repo_path, &["cat-file", "-e", &commit_object]).is_ok();
+    // Starting from a remote-tracking branch also sets it as the upstream
+    let start = match existence.remote {
+        Some(remote) => format!("{}/{}", remote, branch),
+        None if commit_exists() => commit.clone(),
+        None => {
+            return Err(format!(
+                "Branch {} isn't on any remote and its commit is missing",
+                branch
+            ))
+        }
+    };
+    Ok(CreateWorktreeOptions {
+        path,
+        new_branch: Some(branch),
+        commit_ish: Some(start),
+        detach: false,
+    })
+}
+
+/// Count commits only reachable from a local branch, i.e. those that would be
+/// lost if it were deleted
+fn count_unpushed_commits(repo_path: &str, branch: &str) -> Result<u32, String> {
+    let branch_ref = format!("refs/heads/{}", branch);
+    // Patterns excluded from --branches are relative to refs/heads
+    let exclude = format!("--exclude={}", branch);
+    let output = run_git(
+        repo_path,
+        &[
+            "rev-list",
+            "--count",
+            &branch_ref,
+            "--not",
+            "--remotes",
+            &exclude,
+            "--branches",
+        ],
+    )?;
+    output
+        .trim()
+        .parse()
+        .map_err(|e| format!("Failed to count unpushed commits: {}", e))
 }
 
 /// Prune stale worktree references
@@ -653,6 +2449,585 @@ pub fn list_branches(repo_path: &str) -> Result<Vec<BranchInfo>, String> {
     Ok(branches)
 }
 
+/// List the branches that contain a commit, e.g. to confirm a worktree's work
+/// is preserved elsewhere before deleting it. Remote-tracking branches are
+/// only checked when `include_remotes` is set.
+pub fn branches_containing(
+    repo_path: &str,
+    sha: &str,
+    include_remotes: bool,
+) -> Result<BranchesContaining, String> {
+    let sha = verify_commit(repo_path, sha)?;
+    let mut args = vec!["branch", "--format=%(refname)", "--contains", &sha];
+    if include_remotes {
+        args.push("--all");
+    }
+    let output = run_git(repo_path, &args)?;
+    Ok(parse_branches_containing(&output))
+}
+
+/// Split full ref names from `git branch --format=%(refname)` into local and
+/// remote branches, skipping symbolic remote HEADs
+fn parse_branches_containing(output: &str) -> BranchesContaining {
+    let mut branches = BranchesContaining::default();
+    for refname in output.lines().map(str::trim) {
+        if let Some(name) = refname.strip_prefix("refs/heads/") {
+            branches.local.push(name.to_string());
+        } else if let Some(name) = refname.strip_prefix("refs/remotes/") {
+            if !name.ends_with("/HEAD") {
+                branches.remote.push(name.to_string());
+            }
+        }
+    }
+    branches
+}
+
+/// List local branches most recently checked out first, according to the HEAD
+/// reflog. Branches never checked out follow, newest commit first.
+pub fn recent_branches(repo_path: &str, limit: usize) -> Result<Vec<String>, String> {
+    let branches = run_git(
+        repo_path,
+        &[
+            "for-each-ref",
+            "--sort=-committerdate",
+            "--format=%(refname:short)",
+            "refs/heads",
+        ],
+    )?;
+    let branches: Vec<&str> = branches.lines().filter(|b| !b.is_empty()).collect();
+
+    // There's no reflog yet in a repository without commits
+    let reflog =
+        run_git(repo_path, &["reflog", "show", "--format=%gs", "HEAD"]).unwrap_or_default();
+    Ok(order_recent_branches(&reflog, &branches, limit))
+}
+
+/// Order branches by their appearance in reflog subjects (newest first), e.g.
+/// "checkout: moving from main to feature". Entries for deleted branches or
+/// detached commits are dropped.
+fn order_recent_branches(reflog: &str, branches: &[&str], limit: usize) -> Vec<String> {
+    let checked_out = reflog
+        .lines()
+        .filter_map(|line| line.strip_prefix("checkout: moving from "))
+        .filter_map(|rest| rest.split_once(" to "))
+        .flat_map(|(from, to)| [to, from]);
+
+    let mut recent: Vec<String> = Vec::new();
+    for name in checked_out.chain(branches.iter().copied()) {
+        if recent.len() >= limit {
+            break;
+        }
+        if branches.contains(&name) && !recent.iter().any(|r| r == name) {
+            recent.push(name.to_string());
+        }
+    }
+    recent
+}
+
+/// Rename the branch checked out in a worktree. Git carries the upstream
+/// config over, still tracking the old remote branch; with `set_upstream` it
+/// is pointed at the new name on the same remote instead, for the next push.
+pub fn rename_current_branch(
+    worktree_path: &str,
+    new_name: &str,
+    set_upstream: bool,
+) -> Result<BranchInfo, String> {
+    run_git(worktree_path, &["check-ref-format", "--branch", new_name])
+        .map_err(|_| format!("Invalid branch name: {}", new_name))?;
+
+    let old_name = run_git(worktree_path, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .map(|b| b.trim().to_string())
+        .map_err(|_| "Cannot rename a detached HEAD".to_string())?;
+
+    if let Some(other) = list_worktree_records(worktree_path)?
+        .into_iter()
+        .find(|r| r.branch.as_deref() == Some(new_name))
+    {
+        return Err(format!(
+            "{} is already checked out in {}",
+            new_name,
+            other.path.display()
+        ));
+    }
+
+    run_git(worktree_path, &["branch", "-m", &old_name, new_name])?;
+
+    if set_upstream {
+        let remote_key = format!("branch.{}.remote", new_name);
+        if get_config_value(worktree_path, &remote_key)?.is_some() {
+            let merge_key = format!("branch.{}.merge", new_name);
+            let merge_ref = format!("refs/heads/{}", new_name);
+            run_git(worktree_path, &["config", &merge_key, &merge_ref])?;
+        }
+    }
+
+    Ok(BranchInfo {
+        name: new_name.to_string(),
+        is_remote: false,
+        is_checked_out: true,
+    })
+}
+
+/// Check whether a branch exists locally and on a remote. By default remotes are
+/// checked against the remote-tracking refs from the last fetch; with
+/// `query_remotes` each remote is asked directly (`git ls-remote`), which is
+/// up to date but needs the network.
+pub fn branch_exists(
+    repo_path: &str,
+    name: &str,
+    query_remotes: bool,
+) -> Result<BranchExistence, String> {
+    // Also rejects names that could be mistaken for options or other refs
+    run_git(repo_path, &["check-ref-format", "--branch", name])?;
+
+    let ref_exists =
+        |refname: &str| run_git(repo_path, &["show-ref", "--verify", "--quiet", refname]).is_ok();
+
+    let local = ref_exists(&format!("refs/heads/{}", name));
+
+    let remotes = run_git(repo_path, &["remote"])?;
+    let mut remote = None;
+    for candidate in remotes.lines().map(str::trim).filter(|r| !r.is_empty()) {
+        let found = if query_remotes {
+            let output = run_git(
+                repo_path,
+                &[
+                    "ls-remote",
+                    "--heads",
+                    candidate,
+                    &format!("refs/heads/{}", name),
+                ],
+            )?;
+            !output.trim().is_empty()
+        } else {
+            ref_exists(&format!("refs/remotes/{}/{}", candidate, name))
+        };
+
+        if found {
+            remote = Some(candidate.to_string());
+            break;
+        }
+    }
+
+    Ok(BranchExistence { local, remote })
+}
+
+/// Remote default branches resolved with ls-remote, keyed by "<common dir>\0<remote>".
+/// A remote's default branch rarely changes, so one query per session is enough.
+static REMOTE_DEFAULT_BRANCHES: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// Get a remote's default branch (e.g. "main"). The local `<remote>/HEAD` ref
+/// is often missing or stale, so with `query_remote` the remote is asked
+/// directly, falling back to the local ref when it can't be reached.
+pub fn default_branch(
+    repo_path: &str,
+    remote: &str,
+    query_remote: bool,
+) -> Result<Option<String>, String> {
+    if query_remote {
+        let key = format!(
+            "{}\0{}",
+            gitdir_for_worktree(repo_path)?.common_dir.display(),
+            remote
+        );
+        let cache = REMOTE_DEFAULT_BRANCHES.get_or_init(Default::default);
+        if let Some(branch) = cache.lock().map_err(|e| e.to_string())?.get(&key) {
+            return Ok(Some(branch.clone()));
+        }
+
+        match run_git(repo_path, &["ls-remote", "--symref", "--", remote, "HEAD"]) {
+            Ok(output) => {
+                if let Some(branch) = parse_symref_head(&output) {
+                    cache
+                        .lock()
+                        .map_err(|e| e.to_string())?
+                        .insert(key, branch.clone());
+                    return Ok(Some(branch));
+                }
+            }
+            Err(e) => eprintln!("Falling back to local default branch: {}", e),
+        }
+    }
+
+    // Not set unless the repo was cloned or `git remote set-head` was run
+    let local_head = format!("refs/remotes/{}/HEAD", remote);
+    let prefix = format!("{}/", remote);
+    Ok(
+        run_git(repo_path, &["symbolic-ref", "--short", "-q", &local_head])
+            .ok()
+            .and_then(|b| b.trim().strip_prefix(&prefix).map(str::to_string)),
+    )
+}
+
+/// Parse the "ref: refs/heads/main\tHEAD" line of `git ls-remote --symref`
+fn parse_symref_head(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (target, name) = line.strip_prefix("ref: ")?.split_once('\t')?;
+        (name == "HEAD").then(|| {
+            target
+                .strip_prefix("refs/heads/")
+                .unwrap_or(target)
+                .to_string()
+        })
+    })
+}
+
+/// Repository fingerprints, keyed by common dir
+static REPO_FINGERPRINTS: OnceLock<Mutex<HashMap<PathBuf, String>>> = OnceLock::new();
+
+/// A stable identifier for a repository that survives re-cloning and moving
+/// it, for keying per-repository settings. Derived from the origin URL when
+/// there is one, else the root commit, else the toplevel path.
+pub fn repo_fingerprint(repo_path: &str) -> Result<String, String> {
+    let common_dir = gitdir_for_worktree(repo_path)?.common_dir;
+    let cache = REPO_FINGERPRINTS.get_or_init(Default::default);
+    if let Some(fingerprint) = cache.lock().map_err(|e| e.to_string())?.get(&common_dir) {
+        return Ok(fingerprint.clone());
+    }
+
+    let from_remote = run_git(repo_path, &["remote", "get-url", "--", "origin"])
+        .ok()
+        .and_then(|url| remote_fingerprint(url.trim()));
+    let fingerprint = match from_remote {
+        Some(fingerprint) => fingerprint,
+        // With several root commits (e.g. merged histories) the oldest is kept
+        None => match run_git(repo_path, &["rev-list", "--max-parents=0", "HEAD"])
+            .ok()
+            .and_then(|roots| roots.lines().last().map(str::to_string))
+        {
+            Some(root) => format!("root:{}", root),
+            None => format!(
+                "path:{}",
+                run_git(repo_path, &["rev-parse", "--show-toplevel"])?.trim()
+            ),
+        },
+    };
+
+    cache
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(common_dir, fingerprint.clone());
+    Ok(fingerprint)
+}
+
+/// Fingerprint a remote URL so SSH and HTTPS clones of a repository match
+fn remote_fingerprint(url: &str) -> Option<String> {
+    let (host, path) = parse_remote_url(url)?;
+    Some(format!("remote:{}/{}", host.to_lowercase(), path))
+}
+
+/// Build the URL for opening a pull request (GitHub) or merge request (GitLab)
+/// from the worktree's branch into the remote's default branch. Returns None
+/// for a detached HEAD, the default branch itself, or an unrecognized host.
+pub fn pr_url(worktree_path: &str) -> Result<Option<String>, String> {
+    let Some(branch) = run_git(worktree_path, &["symbolic-ref", "--short", "-q", "HEAD"])
+        .ok()
+        .map(|b| b.trim().to_string())
+        .filter(|b| !b.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    let remote = get_config_value(worktree_path, &format!("branch.{}.remote", branch))?
+        .unwrap_or_else(|| "origin".to_string());
+    let Ok(url) = run_git(
+        worktree_path,
+        &["remote", "get-url", "--push", "--", &remote],
+    ) else {
+        return Ok(None);
+    };
+
+    let base = default_branch(worktree_path, &remote, false)?;
+    if base.as_deref() == Some(branch.as_str()) {
+        return Ok(None);
+    }
+    Ok(build_pr_url(url.trim(), &branch, base.as_deref()))
+}
+
+/// Split a remote URL into its host and repository path, e.g.
+/// "git@github.com:owner/repo.git" -> ("github.com", "owner/repo")
+fn parse_remote_url(url: &str) -> Option<(String, String)> {
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => rest.split_once('/')?,
+        // scp-like syntax: [user@]host:path
+        None => url.split_once(':')?,
+    };
+    let host = host.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    (!host.is_empty() && !path.is_empty()).then(|| (host.to_string(), path.to_string()))
+}
+
+/// Percent-encode a branch name for use in a URL, keeping '/' when it can
+/// appear as a path separator
+fn encode_url_component(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Build a new pull/merge request URL for a remote, if its host is recognized
+fn build_pr_url(remote_url: &str, branch: &str, base: Option<&str>) -> Option<String> {
+    let (host, path) = parse_remote_url(remote_url)?;
+
+    if host.contains("github") {
+        let branch = encode_url_component(branch, true);
+        let range = match base {
+            Some(base) => format!("{}...{}", encode_url_component(base, true), branch),
+            None => branch,
+        };
+        Some(format!(
+            "https://{}/{}/compare/{}?expand=1",
+            host, path, range
+        ))
+    } else if host.contains("gitlab") {
+        let mut url = format!(
+            "https://{}/{}/-/merge_requests/new?merge_request%5Bsource_branch%5D={}",
+            host,
+            path,
+            encode_url_component(branch, false)
+        );
+        if let Some(base) = base {
+            url.push_str(&format!(
+                "&merge_request%5Btarget_branch%5D={}",
+                encode_url_component(base, false)
+            ));
+        }
+        Some(url)
+    } else {
+        None
+    }
+}
+
+/// List a repository's remotes with their fetch and push URLs
+pub fn list_remotes(repo_path: &str) -> Result<Vec<Remote>, String> {
+    let output = run_git(repo_path, &["remote", "-v"])?;
+    Ok(parse_remotes(&output))
+}
+
+/// Delete remote-tracking branches whose branch no longer exists on the remote,
+/// optionally fetching first. Returns the pruned refs, e.g. "origin/feature".
+pub fn prune_remote(repo_path: &str, remote: &str, fetch: bool) -> Result<Vec<String>, String> {
+    if fetch {
+        // Fetch without pruning (even if fetch.prune is set) so that
+        // `remote prune` reports everything that was removed
+        run_git(repo_path, &["fetch", "--no-prune", "--", remote])?;
+    }
+    let output = run_git(repo_path, &["remote", "prune", "--", remote])?;
+    Ok(parse_pruned_refs(&output))
+}
+
+/// Parse the " * [pruned] origin/feature" lines of `git remote prune` output
+fn parse_pruned_refs(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("* [pruned] "))
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+/// Parse `git remote -v` output, which lists each remote twice:
+/// "origin\tgit@host:repo.git (fetch)" and "origin\tgit@host:repo.git (push)"
+fn parse_remotes(output: &str) -> Vec<Remote> {
+    let mut remotes: Vec<Remote> = Vec::new();
+
+    for line in output.lines() {
+        let Some((name, rest)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some((url, kind)) = rest.rsplit_once(' ') else {
+            continue;
+        };
+
+        let index = match remotes.iter().position(|r| r.name == name) {
+            Some(index) => index,
+            None => {
+                remotes.push(Remote {
+                    name: name.to_string(),
+                    fetch_url: String::new(),
+                    push_url: String::new(),
+                });
+                remotes.len() - 1
+            }
+        };
+
+        match kind {
+            "(fetch)" => remotes[index].fetch_url = url.to_string(),
+            "(push)" => remotes[index].push_url = url.to_string(),
+            _ => {}
+        }
+    }
+
+    remotes
+}
+
+/// Total size in bytes of the files under a directory (symlinks are not followed)
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Get the size in bytes of the repository's shared object store
+pub fn object_store_size(repo_path: &str) -> Result<u64, String> {
+    let objects_dir = gitdir_for_worktree(repo_path)?.common_dir.join("objects");
+    Ok(dir_size(&objects_dir))
+}
+
+/// Run `git gc` (optionally `--aggressive`), passing each line of git's stderr output to `on_progress`.
+/// Some git versions only print progress meters when attached to a terminal,
+/// in which case only warnings and summary lines come through.
+pub fn run_gc(
+    repo_path: &str,
+    aggressive: bool,
+    mut on_progress: impl FnMut(&str),
+) -> Result<GcResult, String> {
+    use std::io::Read;
+    use std::process::Stdio;
+
+    // gc repacks objects shared by every worktree, so don't run it under any of
+    // them mid-operation. Worktrees whose directory is gone can't be checked.
+    for path in list_worktree_paths(repo_path)? {
+        let path = path.to_string_lossy();
+        if let Ok(Some(operation)) = detect_operation(&path) {
+            return Err(format!(
+                "Cannot run gc while a {} is in progress in {}",
+                operation_command(operation),
+                path
+            ));
+        }
+    }
+
+    let size_before = object_store_size(repo_path)?;
+
+    let mut args = vec!["-C", repo_path, "gc"];
+    if aggressive {
+        args.push("--aggressive");
+    }
+
+    let mut child = Command::new("git")
+        .args(&args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run git gc: {}", e))?;
+
+    // Progress lines are terminated by \r while they update in place, \n once done
+    let mut stderr = child
+        .stderr
+        .take()
+        .ok_or("Failed to capture git gc output")?;
+    let mut output = Vec::new();
+    let mut line = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = stderr
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read git gc output: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buf[..n] {
+            if byte == b'\r' || byte == b'\n' {
+                let text = String::from_utf8_lossy(&line);
+                let text = text.trim();
+                if !text.is_empty() {
+                    on_progress(text);
+                }
+                output.append(&mut line);
+                output.push(b'\n');
+            } else {
+                line.push(byte);
+            }
+        }
+    }
+    output.append(&mut line);
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("Failed to run git gc: {}", e))?;
+    if !status.success() {
+        return Err(format!(
+            "git gc failed: {}",
+            String::from_utf8_lossy(&output)
+        ));
+    }
+
+    Ok(GcResult {
+        size_before,
+        size_after: object_store_size(repo_path)?,
+    })
+}
+
+/// Maximum number of repositories returned by discover_repos
+const MAX_DISCOVERED_REPOS: usize = 500;
+
+/// Directory names never descended into while discovering repositories
+const DISCOVER_SKIP_DIRS: &[&str] = &["node_modules", "target"];
+
+/// Find git repositories under root, descending at most max_depth levels.
+/// A directory containing a .git file or directory counts as a repository,
+/// and its contents are not searched further so nested submodules are not
+/// reported as top-level repositories.
+pub fn discover_repos(root: &str, max_depth: usize) -> Result<Vec<String>, String> {
+    let root_path = Path::new(root);
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let mut repos: Vec<String> = Vec::new();
+    let mut pending: Vec<(PathBuf, usize)> = vec![(root_path.to_path_buf(), 0)];
+
+    while let Some((dir, depth)) = pending.pop() {
+        if dir.join(".git").exists() {
+            repos.push(dir.to_string_lossy().to_string());
+            if repos.len() >= MAX_DISCOVERED_REPOS {
+                break;
+            }
+            continue;
+        }
+
+        if depth >= max_depth {
+            continue;
+        }
+
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue; // Unreadable directories are skipped
+        };
+
+        for entry in entries.flatten() {
+            // file_type() does not follow symlinks, which avoids cycles
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if is_dir && !name.starts_with('.') && !DISCOVER_SKIP_DIRS.contains(&name.as_ref()) {
+                pending.push((entry.path(), depth + 1));
+            }
+        }
+    }
+
+    repos.sort();
+    Ok(repos)
+}
+
 /// Parse git status --porcelain output into WorktreeStatus
 /// Extracted for testability
 fn parse_status_porcelain(output: &str) -> WorktreeStatus {
@@ -696,7 +3071,118 @@ fn parse_status_porcelain(output: &str) -> WorktreeStatus {
         staged,
         untracked,
         conflicted,
+        operation: None,
+    }
+}
+
+/// Parse git status --porcelain output into ChangeCounts.
+/// A file with both staged and unstaged edits (e.g. "MM") counts in both;
+/// unmerged (conflicted) files count as unstaged.
+fn parse_change_counts(output: &str) -> ChangeCounts {
+    let mut counts = ChangeCounts {
+        staged: 0,
+        unstaged: 0,
+        untracked: 0,
+    };
+
+    for line in output.lines() {
+        if line.len() < 2 {
+            continue;
+        }
+
+        let index_status = line.chars().next().unwrap_or(' ');
+        let worktree_status = line.chars().nth(1).unwrap_or(' ');
+
+        match (index_status, worktree_status) {
+            ('?', '?') => counts.untracked += 1,
+            ('!', '!') => {}
+            ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => counts.unstaged += 1,
+            _ => {
+                if index_status != ' ' {
+                    counts.staged += 1;
+                }
+                if worktree_status != ' ' {
+                    counts.unstaged += 1;
+                }
+            }
+        }
     }
+
+    counts
+}
+
+/// Parse `git diff --shortstat` output, e.g.
+/// " 12 files changed, 340 insertions(+), 58 deletions(-)". Parts with no
+/// changes are omitted by git, and the output is empty when nothing changed.
+fn parse_shortstat(output: &str) -> DiffStats {
+    let mut stats = DiffStats {
+        files_changed: 0,
+        insertions: 0,
+        deletions: 0,
+    };
+
+    for part in output.trim().split(',') {
+        let mut words = part.split_whitespace();
+        let Some(count) = words.next().and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        match words.next() {
+            Some(w) if w.starts_with("file") => stats.files_changed = count,
+            Some(w) if w.starts_with("insertion") => stats.insertions = count,
+            Some(w) if w.starts_with("deletion") => stats.deletions = count,
+            _ => {}
+        }
+    }
+
+    stats
+}
+
+/// Parse the stderr of `git apply --verbose` into the files that apply
+/// cleanly and the conflicts. A failed hunk reports both
+/// "error: patch failed: <path>:<line>" and "error: <path>: patch does not apply".
+fn parse_apply_output(output: &str) -> (Vec<String>, Vec<PatchConflict>) {
+    let mut checked = Vec::new();
+    let mut conflicts: Vec<PatchConflict> = Vec::new();
+
+    for line in output.lines() {
+        if let Some(path) = line
+            .strip_prefix("Checking patch ")
+            .and_then(|rest| rest.strip_suffix("..."))
+        {
+            checked.push(path.to_string());
+            continue;
+        }
+
+        let Some(error) = line.strip_prefix("error: ") else {
+            continue;
+        };
+        if let Some((path, line)) = error
+            .strip_prefix("patch failed: ")
+            .and_then(|rest| rest.rsplit_once(':'))
+        {
+            conflicts.push(PatchConflict {
+                path: path.to_string(),
+                line: line.parse().ok(),
+                message: "patch does not apply".to_string(),
+            });
+        } else if let Some((path, message)) = error.split_once(": ") {
+            if conflicts.iter().any(|c| c.path == path) {
+                continue;
+            }
+            conflicts.push(PatchConflict {
+                path: path.to_string(),
+                line: None,
+                message: message.to_string(),
+            });
+        }
+        // Other errors (e.g. "while searching for:") introduce context lines
+    }
+
+    let clean_files = checked
+        .into_iter()
+        .filter(|path| !conflicts.iter().any(|c| &c.path == path))
+        .collect();
+    (clean_files, conflicts)
 }
 
 /// Parse git log output with record/unit separators into Vec<CommitInfo>
@@ -719,27 +3205,202 @@ fn parse_commit_log(output: &str) -> Vec<CommitInfo> {
         let short_hash = fields[1].to_string();
         let author_name = fields[2].to_string();
         let author_email = fields[3].to_string();
-        let timestamp = fields[4].parse::<i64>().unwrap_or(0);
+        let commit_timestamp = fields[4].parse::<i64>().unwrap_or(0);
         let summary = fields[5].to_string();
         let message = fields.get(6).unwrap_or(&"").trim().to_string();
 
-        commits.push(CommitInfo {
-            hash,
-            short_hash,
-            author_name,
-            author_email,
-            timestamp,
-            message,
-            summary,
-        });
+        commits.push(CommitInfo {
+            hash,
+            short_hash,
+            author_name,
+            author_email,
+            commit_timestamp,
+            message,
+            summary,
+        });
+    }
+
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ==================== worktree porcelain tests ====================
+
+    #[test]
+    fn test_parse_worktree_porcelain() {
+        let output = "worktree /repo\nHEAD abc123\nbranch refs/heads/main\n\n\
+                      worktree /wt/my feature\nHEAD def456\ndetached\nlocked on usb\n\n\
+                      worktree /wt/gone\nHEAD 789abc\nbranch refs/heads/fix/bug\n\
+                      prunable gitdir file points to non-existent location\n\n";
+        let records = parse_worktree_porcelain(output);
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].path, PathBuf::from("/repo"));
+        assert_eq!(records[0].head.as_deref(), Some("abc123"));
+        assert_eq!(records[0].branch.as_deref(), Some("main"));
+        assert_eq!(records[1].path, PathBuf::from("/wt/my feature"));
+        assert!(records[1].detached && records[1].locked);
+        assert_eq!(records[1].branch, None);
+        assert_eq!(records[2].branch.as_deref(), Some("fix/bug"));
+        assert!(records[2].prunable.is_some());
+    }
+
+    #[test]
+    fn test_parse_worktree_porcelain_path_with_spaces() {
+        let output = "worktree /Users/me/My Projects/repo\nHEAD abc123\nbranch refs/heads/main\n\n\
+                      worktree /Users/me/My Projects/repo wt  two\nHEAD def456\nbranch refs/heads/x\n";
+        let paths: Vec<PathBuf> = parse_worktree_porcelain(output)
+            .into_iter()
+            .map(|r| r.path)
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/Users/me/My Projects/repo"),
+                PathBuf::from("/Users/me/My Projects/repo wt  two"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_worktree_porcelain_bare() {
+        let records = parse_worktree_porcelain("worktree /repo.git\nbare\n");
+        assert_eq!(records.len(), 1);
+        assert!(records[0].bare);
+        assert_eq!(records[0].head, None);
+    }
+
+    #[test]
+    fn test_find_worktree_conflicts() {
+        let record = |path: &str, branch: Option<&str>| WorktreeRecord {
+            path: PathBuf::from(path),
+            branch: branch.map(str::to_string),
+            ..Default::default()
+        };
+        let records = vec![
+            record("/repo", Some("main")),
+            record("/wt/a", Some("feature")),
+            record("/wt/b", Some("feature")),
+            record("/wt/c", None),
+            record("/wt/missing", Some("other")),
+        ];
+
+        let conflicts = find_worktree_conflicts(&records, |p| p != Path::new("/wt/missing"));
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].kind, WorktreeConflictKind::DuplicateBranch);
+        assert_eq!(conflicts[0].branch.as_deref(), Some("feature"));
+        assert_eq!(
+            conflicts[0].paths,
+            vec![PathBuf::from("/wt/a"), PathBuf::from("/wt/b")]
+        );
+        assert_eq!(conflicts[1].kind, WorktreeConflictKind::MissingGitdir);
+        assert_eq!(conflicts[1].paths, vec![PathBuf::from("/wt/missing")]);
+    }
+
+    #[test]
+    fn test_build_worktree_info_missing_directory() {
+        let record = WorktreeRecord {
+            path: PathBuf::from("/nonexistent/woodeye/wt"),
+            head: Some("0123456789abcdef".to_string()),
+            branch: Some("feature".to_string()),
+            ..Default::default()
+        };
+        let worktree = build_worktree_info("/nonexistent/woodeye", &record, false).unwrap();
+        assert!(!worktree.exists_on_disk);
+        assert_eq!(worktree.name, "wt");
+        assert_eq!(worktree.head.branch.as_deref(), Some("feature"));
+        // Without a repository to abbreviate it, the full SHA is kept
+        assert_eq!(worktree.head.commit_sha, "0123456789abcdef");
+    }
+
+    #[test]
+    fn test_build_worktree_info_short_sha() {
+        let repo = init_temp_repo("short-sha");
+        let repo_path = repo.to_string_lossy().to_string();
+        git_in(&repo, &["config", "core.abbrev", "12"]);
+        git_in(&repo, &["commit", "-q", "--allow-empty", "-m", "first"]);
+
+        let worktrees = get_all_worktrees(&repo_path).unwrap();
+        assert_eq!(worktrees[0].head.commit_sha.len(), 12);
+        assert_eq!(worktrees[0].head.commit_message, "first");
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    // ==================== operation detection tests ====================
+
+    #[test]
+    fn test_operation_from_git_dir() {
+        let git_dir = std::env::temp_dir().join(format!("woodeye-op-{}", std::process::id()));
+        fs::create_dir_all(&git_dir).unwrap();
+        assert_eq!(operation_from_git_dir(&git_dir), None);
+
+        fs::write(git_dir.join("CHERRY_PICK_HEAD"), "abc\n").unwrap();
+        assert_eq!(
+            operation_from_git_dir(&git_dir),
+            Some(GitOperation::CherryPick)
+        );
+
+        // A rebase that stops on a cherry-picked commit reports as the rebase
+        fs::create_dir_all(git_dir.join("rebase-merge")).unwrap();
+        assert_eq!(operation_from_git_dir(&git_dir), Some(GitOperation::Rebase));
+
+        fs::remove_dir_all(&git_dir).unwrap();
+    }
+
+    #[test]
+    fn test_operation_progress_from_git_dir() {
+        let git_dir = std::env::temp_dir().join(format!("woodeye-progress-{}", std::process::id()));
+        let rebase_dir = git_dir.join("rebase-merge");
+        fs::create_dir_all(&rebase_dir).unwrap();
+
+        // Counts are missing until git writes them
+        let progress = operation_progress_from_git_dir(&git_dir).unwrap();
+        assert_eq!(progress.operation, GitOperation::Rebase);
+        assert_eq!((progress.current, progress.total), (None, None));
+
+        fs::write(rebase_dir.join("msgnum"), "3\n").unwrap();
+        fs::write(rebase_dir.join("end"), "7\n").unwrap();
+        let progress = operation_progress_from_git_dir(&git_dir).unwrap();
+        assert_eq!((progress.current, progress.total), (Some(3), Some(7)));
+
+        fs::remove_dir_all(&git_dir).unwrap();
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        let conflicted = "a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> side\nb\n";
+        assert!(has_conflict_markers(conflicted));
+        assert!(!has_conflict_markers("a\nresolved\nb\n"));
+        // A lone separator line (e.g. a Markdown heading underline) is not a conflict
+        assert!(!has_conflict_markers("Title\n=======\n"));
     }
 
-    commits
-}
+    #[test]
+    fn test_clean_state() {
+        let counts = |staged, unstaged, untracked| ChangeCounts {
+            staged,
+            unstaged,
+            untracked,
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let state = clean_state(&counts(0, 0, 0), None);
+        assert!(state.clean);
+        assert!(state.reasons.is_empty());
+
+        let state = clean_state(&counts(1, 0, 2), Some(GitOperation::CherryPick));
+        assert!(!state.clean);
+        assert_eq!(
+            state.reasons,
+            vec![
+                "1 staged change(s)",
+                "2 untracked file(s)",
+                "cherry-pick in progress"
+            ]
+        );
+    }
 
     // ==================== parse_range tests ====================
 
@@ -934,6 +3595,13 @@ Binary files /dev/null and b/image.png differ
         assert!(files[0].hunks.is_empty());
     }
 
+    #[test]
+    fn test_is_binary_content() {
+        assert!(is_binary_content(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(!is_binary_content(b"fn main() {}\n"));
+        assert!(!is_binary_content(b""));
+    }
+
     #[test]
     fn test_parse_diff_multiple_files() {
         let diff = r#"diff --git a/file1.rs b/file1.rs
@@ -981,6 +3649,50 @@ index abc..def 100644
         assert_eq!(files[0].hunks[1].old_start, 10);
     }
 
+    // ==================== discover_repos tests ====================
+
+    #[test]
+    fn test_discover_repos_skips_nested_and_respects_depth() {
+        let root = std::env::temp_dir().join(format!("woodeye-discover-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("a/.git")).unwrap();
+        fs::create_dir_all(root.join("a/submodule/.git")).unwrap();
+        fs::create_dir_all(root.join("group/b")).unwrap();
+        fs::write(root.join("group/b/.git"), "gitdir: elsewhere").unwrap();
+        fs::create_dir_all(root.join("deep/er/c/.git")).unwrap();
+
+        let root_str = root.to_string_lossy().to_string();
+        let repos = discover_repos(&root_str, 2).unwrap();
+        let expected: Vec<String> = vec![
+            root.join("a").to_string_lossy().to_string(),
+            root.join("group/b").to_string_lossy().to_string(),
+        ];
+        assert_eq!(repos, expected);
+
+        let repos = discover_repos(&root_str, 3).unwrap();
+        assert_eq!(repos.len(), 3);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    // ==================== derive_worktree_path tests ====================
+
+    #[test]
+    fn test_derive_worktree_path() {
+        assert_eq!(
+            derive_worktree_path("/home/me/worktrees", "woodeye", "main"),
+            PathBuf::from("/home/me/worktrees/woodeye/main")
+        );
+    }
+
+    #[test]
+    fn test_derive_worktree_path_flattens_slashes() {
+        assert_eq!(
+            derive_worktree_path("/home/me/worktrees", "woodeye", "feature/auth/login"),
+            PathBuf::from("/home/me/worktrees/woodeye/feature-auth-login")
+        );
+    }
+
     // ==================== parse_status_porcelain tests ====================
 
     #[test]
@@ -1057,11 +3769,761 @@ index abc..def 100644
         assert_eq!(status.conflicted, 1);
     }
 
+    // ==================== parse_change_counts tests ====================
+
+    #[test]
+    fn test_change_counts_clean() {
+        let counts = parse_change_counts("");
+        assert_eq!(counts.staged, 0);
+        assert_eq!(counts.unstaged, 0);
+        assert_eq!(counts.untracked, 0);
+    }
+
+    #[test]
+    fn test_change_counts_staged_and_unstaged_same_file() {
+        let counts = parse_change_counts("MM both.rs\n");
+        assert_eq!(counts.staged, 1);
+        assert_eq!(counts.unstaged, 1);
+    }
+
+    #[test]
+    fn test_change_counts_mixed() {
+        let output = "M  staged.rs\n M modified.rs\n D deleted.rs\n?? new.txt\n?? other.txt\nUU conflict.rs\n";
+        let counts = parse_change_counts(output);
+        assert_eq!(counts.staged, 1);
+        assert_eq!(counts.unstaged, 3);
+        assert_eq!(counts.untracked, 2);
+    }
+
+    // ==================== parse_shortstat tests ====================
+
+    #[test]
+    fn test_shortstat_full() {
+        let stats = parse_shortstat(" 12 files changed, 340 insertions(+), 58 deletions(-)\n");
+        assert_eq!(stats.files_changed, 12);
+        assert_eq!(stats.insertions, 340);
+        assert_eq!(stats.deletions, 58);
+    }
+
+    #[test]
+    fn test_shortstat_partial() {
+        let stats = parse_shortstat(" 1 file changed, 1 deletion(-)\n");
+        assert_eq!(stats.files_changed, 1);
+        assert_eq!(stats.insertions, 0);
+        assert_eq!(stats.deletions, 1);
+    }
+
+    #[test]
+    fn test_shortstat_empty() {
+        let stats = parse_shortstat("");
+        assert_eq!(stats.files_changed, 0);
+        assert_eq!(stats.insertions, 0);
+        assert_eq!(stats.deletions, 0);
+    }
+
+    // ==================== order_recent_branches tests ====================
+
+    #[test]
+    fn test_order_recent_branches() {
+        let reflog = "checkout: moving from feat to main\n\
+                      commit: add thing\n\
+                      checkout: moving from main to feat\n\
+                      checkout: moving from gone to main\n\
+                      checkout: moving from main to 1a2b3c4\n";
+        let branches = ["main", "feat", "old", "older"];
+        assert_eq!(
+            order_recent_branches(reflog, &branches, 10),
+            vec!["main", "feat", "old", "older"]
+        );
+        assert_eq!(order_recent_branches(reflog, &branches, 1), vec!["main"]);
+    }
+
+    #[test]
+    fn test_order_recent_branches_without_reflog() {
+        let branches = ["newest", "oldest"];
+        assert_eq!(
+            order_recent_branches("", &branches, 10),
+            vec!["newest", "oldest"]
+        );
+    }
+
+    // ==================== truncate_large_diffs tests ====================
+
+    fn file_with_lines(path: &str, lines: &[&str]) -> FileDiff {
+        FileDiff {
+            path: path.to_string(),
+            status: FileStatus::Modified,
+            old_path: None,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: lines.len() as u32,
+                header: String::new(),
+                lines: lines
+                    .iter()
+                    .map(|l| DiffLine {
+                        kind: '+',
+                        content: l.to_string(),
+                    })
+                    .collect(),
+            }],
+            binary: false,
+            truncated: false,
+            is_lfs: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_lfs_check_attr() {
+        let output = "assets/logo.psd\0filter\0lfs\0src/main.rs\0filter\0unspecified\0\
+                      video.mp4\0filter\0lfs\0";
+        let paths = parse_lfs_check_attr(output);
+        assert_eq!(paths.len(), 2);
+        assert!(paths.contains("assets/logo.psd") && paths.contains("video.mp4"));
+    }
+
+    #[test]
+    fn test_parse_lfs_ls_files() {
+        let output = "4d7a2146 * assets/logo v2.psd\n\
+                      9f2b1c3e - video.mp4\n";
+        let files = parse_lfs_ls_files(output);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "assets/logo v2.psd");
+        assert_eq!(files[0].oid, "4d7a2146");
+        assert!(files[0].downloaded);
+        assert!(!files[1].downloaded);
+    }
+
+    #[test]
+    fn test_truncate_large_diffs() {
+        // Each line counts its newline, so "0123456789" is 11 bytes
+        let mut files = vec![
+            file_with_lines("small.rs", &["0123456789"]),
+            file_with_lines("Cargo.lock", &["0123456789"; 10]),
+            file_with_lines("also_small.rs", &["0123456789"]),
+            file_with_lines("over_budget.rs", &["0123456789"]),
+        ];
+        let remaining = truncate_large_diffs(&mut files, 30);
+
+        let truncated: Vec<bool> = files.iter().map(|f| f.truncated).collect();
+        assert_eq!(truncated, vec![false, true, false, true]);
+        assert!(files[1].hunks.is_empty());
+        assert_eq!(remaining, 8);
+    }
+
+    // ==================== tree_children tests ====================
+
+    #[test]
+    fn test_tree_children_root() {
+        let paths = [
+            "Cargo.toml",
+            "src/main.rs",
+            "src/lib.rs",
+            "README.md",
+            "docs/a/b.md",
+            "",
+        ];
+        let entry = |path: &str, is_dir| TreeEntry {
+            path: path.to_string(),
+            is_dir,
+        };
+        assert_eq!(
+            tree_children(paths.into_iter(), ""),
+            vec![
+                entry("docs", true),
+                entry("src", true),
+                entry("Cargo.toml", false),
+                entry("README.md", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tree_children_subpath() {
+        let paths = ["src/main.rs", "src/git/mod.rs", "src-tauri/build.rs"];
+        let children = tree_children(paths.into_iter(), "src");
+        let names: Vec<(&str, bool)> = children
+            .iter()
+            .map(|e| (e.path.as_str(), e.is_dir))
+            .collect();
+        // "src-tauri" shares the prefix but isn't inside "src"
+        assert_eq!(names, vec![("src/git", true), ("src/main.rs", false)]);
+    }
+
+    // ==================== parse_conflict_hunks tests ====================
+
+    #[test]
+    fn test_parse_conflict_hunks_merge_style() {
+        let content = "fn main() {\n\
+                       <<<<<<< HEAD\n\
+                       \x20   println!(\"ours\");\n\
+                       =======\n\
+                       \x20   println!(\"theirs\");\n\
+                       \x20   println!(\"more\");\n\
+                       >>>>>>> feature/login\n\
+                       }\n";
+        assert_eq!(
+            parse_conflict_hunks(content),
+            vec![ConflictHunk {
+                start_line: 2,
+                end_line: 7,
+                ours_label: "HEAD".to_string(),
+                theirs_label: "feature/login".to_string(),
+                ours: "    println!(\"ours\");\n".to_string(),
+                base: None,
+                theirs: "    println!(\"theirs\");\n    println!(\"more\");\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_conflict_hunks_diff3_style() {
+        let content = "<<<<<<< HEAD\r\n\
+                       a\r\n\
+                       ||||||| merged common ancestors\r\n\
+                       base\r\n\
+                       =======\r\n\
+                       >>>>>>> main\r\n\
+                       between\n\
+                       <<<<<<< HEAD\n\
+                       x\n\
+                       =======\n\
+                       y\n\
+                       >>>>>>> main";
+        let hunks = parse_conflict_hunks(content);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].ours, "a\r\n");
+        assert_eq!(hunks[0].base, Some("base\r\n".to_string()));
+        // Deleted on their side
+        assert_eq!(hunks[0].theirs, "");
+        assert_eq!(hunks[0].theirs_label, "main");
+        assert_eq!((hunks[1].start_line, hunks[1].end_line), (8, 12));
+        assert_eq!(hunks[1].base, None);
+    }
+
+    #[test]
+    fn test_parse_conflict_hunks_ignores_unterminated() {
+        let content = "<<<<<<< HEAD\nours\n=======\ntheirs\n";
+        assert!(parse_conflict_hunks(content).is_empty());
+        assert!(parse_conflict_hunks("no conflicts\n").is_empty());
+    }
+
+    // ==================== parse_branches_containing tests ====================
+
+    #[test]
+    fn test_parse_branches_containing() {
+        let output = "refs/heads/feature/login\n\
+                      refs/heads/main\n\
+                      refs/remotes/origin/HEAD\n\
+                      refs/remotes/origin/feature/login\n";
+        assert_eq!(
+            parse_branches_containing(output),
+            BranchesContaining {
+                local: vec!["feature/login".to_string(), "main".to_string()],
+                remote: vec!["origin/feature/login".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_branches_containing_detached() {
+        // A detached HEAD containing the commit is listed by name only
+        let output = "(HEAD detached at 1a2b3c4)\nrefs/heads/main\n";
+        assert_eq!(
+            parse_branches_containing(output).local,
+            vec!["main".to_string()]
+        );
+    }
+
+    // ==================== parse_apply_output tests ====================
+
+    #[test]
+    fn test_parse_apply_output() {
+        let output = "Checking patch a.txt...\n\
+                      error: while searching for:\n\
+                      25\n\
+                      26\n\
+                      \n\
+                      error: patch failed: a.txt:25\n\
+                      error: a.txt: patch does not apply\n\
+                      Checking patch b.txt...\n\
+                      Checking patch new.txt...\n\
+                      error: new.txt: already exists in working directory\n";
+        let (clean_files, conflicts) = parse_apply_output(output);
+
+        assert_eq!(clean_files, vec!["b.txt".to_string()]);
+        assert_eq!(
+            conflicts,
+            vec![
+                PatchConflict {
+                    path: "a.txt".to_string(),
+                    line: Some(25),
+                    message: "patch does not apply".to_string(),
+                },
+                PatchConflict {
+                    path: "new.txt".to_string(),
+                    line: None,
+                    message: "already exists in working directory".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_apply_output_clean() {
+        let output = "Checking patch a.txt...\nApplied patch a.txt cleanly.\n";
+        let (clean_files, conflicts) = parse_apply_output(output);
+        assert_eq!(clean_files, vec!["a.txt".to_string()]);
+        assert!(conflicts.is_empty());
+    }
+
+    // ==================== build_pr_url tests ====================
+
+    #[test]
+    fn test_parse_remote_url() {
+        let expected = Some(("github.com".to_string(), "owner/repo".to_string()));
+        assert_eq!(parse_remote_url("git@github.com:owner/repo.git"), expected);
+        assert_eq!(parse_remote_url("https://github.com/owner/repo"), expected);
+        assert_eq!(
+            parse_remote_url("ssh://git@github.com:22/owner/repo.git"),
+            expected
+        );
+        assert_eq!(parse_remote_url("/srv/git/repo.git"), None);
+    }
+
+    fn init_temp_repo(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("woodeye-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let status = std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        dir.canonicalize().unwrap()
+    }
+
+    /// Run git in `dir` with a throwaway identity, panicking on failure
+    fn git_in(dir: &Path, args: &[&str]) {
+        let output = std::process::Command::new("git")
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_diff_summary_rejects_options() {
+        let dir = init_temp_repo("diff-summary");
+        let repo_path = dir.to_string_lossy().to_string();
+        git_in(&dir, &["commit", "-q", "--allow-empty", "-m", "initial"]);
+
+        let output = dir.join("out.txt");
+        let from = format!("--output={}", output.display());
+        assert!(diff_summary(&repo_path, &from, None).is_err());
+        assert!(diff_summary(&repo_path, "HEAD", Some(&from)).is_err());
+        assert!(!output.exists());
+        assert!(diff_summary(&repo_path, "HEAD", Some("HEAD")).is_ok());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_patch_unborn_head() {
+        let dir = init_temp_repo("export-unborn");
+        fs::write(dir.join("a.txt"), "hello\n").unwrap();
+        git_in(&dir, &["add", "a.txt"]);
+
+        let patch = export_patch(dir.to_str().unwrap(), false).unwrap();
+        assert!(patch.contains("+++ b/a.txt"));
+        assert!(patch.contains("+hello"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    /// A clone of a bare "origin" with one pushed commit on main
+    fn init_cloned_repo(name: &str) -> (PathBuf, PathBuf) {
+        let dir = init_temp_repo(name);
+        let clone = dir.join("clone");
+        git_in(&dir, &["init", "-q", "--bare", "-b", "main", "origin.git"]);
+        git_in(&dir, &["clone", "-q", "origin.git", "clone"]);
+        git_in(&clone, &["checkout", "-q", "-b", "main"]);
+        git_in(&clone, &["commit", "-q", "--allow-empty", "-m", "initial"]);
+        git_in(&clone, &["push", "-q", "origin", "main"]);
+        (dir, clone)
+    }
+
+    #[test]
+    fn test_count_unpushed_commits() {
+        let (dir, clone) = init_cloned_repo("unpushed");
+        let repo_path = clone.to_string_lossy().to_string();
+
+        git_in(&clone, &["checkout", "-q", "-b", "feature"]);
+        assert_eq!(count_unpushed_commits(&repo_path, "feature").unwrap(), 0);
+
+        git_in(&clone, &["commit", "-q", "--allow-empty", "-m", "one"]);
+        git_in(&clone, &["commit", "-q", "--allow-empty", "-m", "two"]);
+        assert_eq!(count_unpushed_commits(&repo_path, "feature").unwrap(), 2);
+
+        // Commits also on another local branch aren't lost with this one
+        git_in(&clone, &["branch", "feature-copy"]);
+        assert_eq!(count_unpushed_commits(&repo_path, "feature").unwrap(), 0);
+        git_in(&clone, &["branch", "-D", "feature-copy"]);
+
+        git_in(&clone, &["push", "-q", "origin", "feature"]);
+        assert_eq!(count_unpushed_commits(&repo_path, "feature").unwrap(), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_squash_head_stays_on_branch() {
+        let (dir, clone) = init_cloned_repo("squash");
+        let repo_path = clone.to_string_lossy().to_string();
+        git_in(&clone, &["config", "user.name", "Test"]);
+        git_in(&clone, &["config", "user.email", "test@example.com"]);
+
+        // History on main that the feature branch mustn't swallow
+        git_in(&clone, &["commit", "-q", "--allow-empty", "-m", "main two"]);
+        git_in(&clone, &["push", "-q", "origin", "main"]);
+        git_in(&clone, &["remote", "set-head", "origin", "main"]);
+        git_in(&clone, &["checkout", "-q", "-b", "feature"]);
+        for name in ["one", "two"] {
+            fs::write(clone.join(name), name).unwrap();
+            git_in(&clone, &["add", name]);
+            git_in(&clone, &["commit", "-q", "-m", name]);
+        }
+
+        let err = squash_head(&repo_path, 3, "squashed", false).unwrap_err();
+        assert!(err.contains("at most 2"), "{}", err);
+
+        let info = squash_head(&repo_path, 2, "squashed", false).unwrap();
+        assert_eq!(info.summary, "squashed");
+        assert_eq!(commits_ahead_of(&repo_path, None).unwrap(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_squash_head_on_default_branch() {
+        let (dir, clone) = init_cloned_repo("squash-main");
+        let repo_path = clone.to_string_lossy().to_string();
+        git_in(&clone, &["config", "user.name", "Test"]);
+        git_in(&clone, &["config", "user.email", "test@example.com"]);
+        git_in(&clone, &["remote", "set-head", "origin", "main"]);
+
+        for name in ["one", "two"] {
+            fs::write(clone.join(name), name).unwrap();
+            git_in(&clone, &["add", name]);
+            git_in(&clone, &["commit", "-q", "-m", name]);
+        }
+
+        let info = squash_head(&repo_path, 2, "squashed", false).unwrap();
+        assert_eq!(info.summary, "squashed");
+        // Pushed commits are still protected
+        git_in(&clone, &["push", "-q", "origin", "main"]);
+        git_in(&clone, &["commit", "-q", "--allow-empty", "-m", "three"]);
+        let err = squash_head(&repo_path, 2, "squashed", false).unwrap_err();
+        assert!(err.contains("already pushed"), "{}", err);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_delete_worktree_keeps_unpushed_branch() {
+        let (dir, clone) = init_cloned_repo("delete-branch");
+        let repo_path = clone.to_string_lossy().to_string();
+        let worktree = dir.join("wt");
+        let worktree_path = worktree.to_string_lossy().to_string();
+
+        git_in(
+            &clone,
+            &["worktree", "add", "-q", "-b", "feature", &worktree_path],
+        );
+        git_in(
+            &worktree,
+            &["commit", "-q", "--allow-empty", "-m", "unpushed"],
+        );
+
+        let paths = vec![worktree_path.clone()];
+        let outcomes = delete_worktrees(&repo_path, &paths, false, true);
+        let result = outcomes[0].result.as_ref().unwrap();
+        assert_eq!(result.deleted_branch, None);
+        assert!(result
+            .kept_branch_reason
+            .as_deref()
+            .unwrap()
+            .contains("1 commit(s)"));
+        assert!(branch_exists(&repo_path, "feature", false).unwrap().local);
+        assert!(!worktree.exists());
+
+        // Once pushed, nothing would be lost
+        git_in(&clone, &["push", "-q", "origin", "feature"]);
+        git_in(
+            &clone,
+            &["worktree", "add", "-q", &worktree_path, "feature"],
+        );
+        let outcomes = delete_worktrees(&repo_path, &[worktree_path], false, true);
+        let result = outcomes[0].result.as_ref().unwrap();
+        assert_eq!(result.deleted_branch.as_deref(), Some("feature"));
+        assert!(!branch_exists(&repo_path, "feature", false).unwrap().local);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_delete_worktree_force_deletes_unpushed_branch() {
+        let (dir, clone) = init_cloned_repo("delete-branch-force");
+        let repo_path = clone.to_string_lossy().to_string();
+        let worktree = dir.join("wt");
+        let worktree_path = worktree.to_string_lossy().to_string();
+
+        git_in(
+            &clone,
+            &["worktree", "add", "-q", "-b", "feature", &worktree_path],
+        );
+        git_in(
+            &worktree,
+            &["commit", "-q", "--allow-empty", "-m", "unpushed"],
+        );
+
+        let result = delete_worktree(&repo_path, &worktree_path, true, true).unwrap();
+        assert_eq!(result.deleted_branch.as_deref(), Some("feature"));
+        assert!(!branch_exists(&repo_path, "feature", false).unwrap().local);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_verify_commit_rejects_options() {
+        let repo = init_temp_repo("verify-commit");
+        let repo_path = repo.to_string_lossy().to_string();
+        let status = std::process::Command::new("git")
+            .args(["-c", "user.name=a", "-c", "user.email=a@a"])
+            .args(["commit", "-q", "--allow-empty", "-m", "first"])
+            .current_dir(&repo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let head = verify_commit(&repo_path, "HEAD").unwrap();
+        assert_eq!(head.len(), 40);
+        assert!(verify_commit(&repo_path, "--output=/tmp/x").is_err());
+        assert!(verify_commit(&repo_path, "-h").is_err());
+        assert!(get_file_at_commit(&repo_path, "--help", "file").is_err());
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_validate_worktree_target_existing() {
+        let repo = init_temp_repo("validate-existing");
+        let repo_path = repo.to_string_lossy().to_string();
+
+        let result = validate_worktree_target(&repo_path, &repo_path).unwrap();
+        assert_eq!(result.issue, Some(WorktreeTargetIssue::AlreadyWorktree));
+
+        let occupied = repo.join("occupied");
+        fs::create_dir_all(&occupied).unwrap();
+        fs::write(occupied.join("file"), "x").unwrap();
+        let result = validate_worktree_target(&repo_path, "occupied").unwrap();
+        assert_eq!(result.issue, Some(WorktreeTargetIssue::PathNotEmpty));
+
+        let result = validate_worktree_target(&repo_path, "occupied/file").unwrap();
+        assert_eq!(result.issue, Some(WorktreeTargetIssue::NotADirectory));
+
+        let result = validate_worktree_target(&repo_path, "new/nested").unwrap();
+        assert!(result.valid);
+
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_worktree_target_unwritable_parent() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let repo = init_temp_repo("validate-unwritable");
+        let repo_path = repo.to_string_lossy().to_string();
+        let parent = repo.join("locked");
+        fs::create_dir_all(&parent).unwrap();
+        fs::set_permissions(&parent, fs::Permissions::from_mode(0o555)).unwrap();
+
+        let result = validate_worktree_target(&repo_path, "locked/wt").unwrap();
+        // Permissions don't restrict root, in which case the parent is writable
+        let writable = fs::create_dir(parent.join("probe")).is_ok();
+        if writable {
+            assert!(result.valid);
+        } else {
+            assert_eq!(result.issue, Some(WorktreeTargetIssue::ParentNotWritable));
+        }
+
+        fs::set_permissions(&parent, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_parse_commit_activity() {
+        let output = "\x1e2024-03-02\n\n3\t1\tsrc/a.rs\n-\t-\tlogo.png\n\
+                      \x1e2024-03-02\n\n1\t0\tsrc/b.rs\n\
+                      \x1e2024-03-01\n\x1e2024-03-01\n\n0\t5\tREADME.md\n";
+        assert_eq!(
+            parse_commit_activity(output),
+            vec![
+                DayActivity {
+                    date: "2024-03-01".to_string(),
+                    commits: 2,
+                    insertions: 0,
+                    deletions: 5,
+                },
+                DayActivity {
+                    date: "2024-03-02".to_string(),
+                    commits: 2,
+                    insertions: 4,
+                    deletions: 1,
+                },
+            ]
+        );
+        assert!(parse_commit_activity("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_worktree_layout() {
+        let worktree = |path: &str| {
+            format!(
+                r#"{{"path": "{}", "name": "feature", "is_main": false,
+                    "head": {{"branch": "feature", "commit_sha": "abc123", "commit_message": "wip", "upstream": null}},
+                    "status": null, "last_commit_timestamp": 0, "last_modified": 0,
+                    "is_bare": false, "is_detached": false, "is_locked": false, "exists_on_disk": true}}"#,
+                path
+            )
+        };
+        let layout = |worktrees: String| {
+            format!(
+                r#"{{"repo_path": "/src/repo", "default_branch": "main", "remotes": [], "worktrees": [{}]}}"#,
+                worktrees
+            )
+        };
+
+        let parsed = parse_worktree_layout(&layout(worktree("/src/feature"))).unwrap();
+        assert_eq!(parsed.worktrees.len(), 1);
+        assert_eq!(parsed.worktrees[0].head.branch.as_deref(), Some("feature"));
+
+        assert!(parse_worktree_layout("not json").is_err());
+        assert!(parse_worktree_layout(r#"{"repo_path": "/src/repo", "worktrees": []}"#).is_err());
+        assert!(parse_worktree_layout(&layout(worktree("feature"))).is_err());
+    }
+
+    #[test]
+    fn test_remote_fingerprint() {
+        assert_eq!(
+            remote_fingerprint("git@GitHub.com:owner/repo.git"),
+            remote_fingerprint("https://github.com/owner/repo")
+        );
+        assert_eq!(
+            remote_fingerprint("ssh://git@gitlab.com:22/group/sub/repo.git").as_deref(),
+            Some("remote:gitlab.com/group/sub/repo")
+        );
+        assert_eq!(remote_fingerprint("/srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn test_build_pr_url_github() {
+        let remote = "git@github.com:owner/repo.git";
+        assert_eq!(
+            build_pr_url(remote, "feature/login", Some("main")),
+            Some("https://github.com/owner/repo/compare/main...feature/login?expand=1".to_string())
+        );
+        assert_eq!(
+            build_pr_url(remote, "fix#12", None),
+            Some("https://github.com/owner/repo/compare/fix%2312?expand=1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_gitlab() {
+        let remote = "git@gitlab.com:group/sub/repo.git";
+        assert_eq!(
+            build_pr_url(remote, "feature/login", Some("main")),
+            Some(
+                "https://gitlab.com/group/sub/repo/-/merge_requests/new\
+                 ?merge_request%5Bsource_branch%5D=feature%2Flogin\
+                 &merge_request%5Btarget_branch%5D=main"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_pr_url_unknown_host() {
+        assert_eq!(
+            build_pr_url("git@example.com:owner/repo.git", "feature", Some("main")),
+            None
+        );
+    }
+
+    // ==================== parse_symref_head tests ====================
+
+    #[test]
+    fn test_parse_symref_head() {
+        let output = "ref: refs/heads/main\tHEAD\n1a2b3c4d\tHEAD\n";
+        assert_eq!(parse_symref_head(output), Some("main".to_string()));
+    }
+
+    #[test]
+    fn test_parse_symref_head_missing() {
+        // Remotes without a symbolic HEAD (e.g. empty repositories) only list the hash
+        assert_eq!(parse_symref_head("1a2b3c4d\tHEAD\n"), None);
+        assert_eq!(parse_symref_head(""), None);
+    }
+
+    // ==================== parse_remotes tests ====================
+
+    #[test]
+    fn test_parse_remotes() {
+        let output = "origin\thttps://github.com/me/repo.git (fetch)\n\
+                      origin\tgit@github.com:me/repo.git (push)\n\
+                      upstream\thttps://github.com/them/repo.git (fetch)\n\
+                      upstream\thttps://github.com/them/repo.git (push)\n";
+        let remotes = parse_remotes(output);
+        assert_eq!(remotes.len(), 2);
+        assert_eq!(remotes[0].name, "origin");
+        assert_eq!(remotes[0].fetch_url, "https://github.com/me/repo.git");
+        assert_eq!(remotes[0].push_url, "git@github.com:me/repo.git");
+        assert_eq!(remotes[1].name, "upstream");
+        assert_eq!(remotes[1].push_url, "https://github.com/them/repo.git");
+    }
+
+    #[test]
+    fn test_parse_remotes_empty() {
+        assert!(parse_remotes("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_pruned_refs() {
+        let output = "Pruning origin\nURL: git@github.com:me/repo.git\n * [pruned] origin/feat\n * [pruned] origin/fix/bug\n";
+        assert_eq!(
+            parse_pruned_refs(output),
+            vec!["origin/feat".to_string(), "origin/fix/bug".to_string()]
+        );
+        assert!(parse_pruned_refs("").is_empty());
+    }
+
     // ==================== parse_commit_log tests ====================
 
     #[test]
     fn test_commit_log_single() {
-        // Format: hash, short_hash, author_name, author_email, timestamp, summary, body
+        // Format: hash, short_hash, author_name, author_email, commit_timestamp, summary, body
         let output = "abc123def456\x1fabc123\x1fJohn Doe\x1fjohn@example.com\x1f1700000000\x1fFix bug\x1fDetailed description\x1e";
         let commits = parse_commit_log(output);
         assert_eq!(commits.len(), 1);
@@ -1069,7 +4531,7 @@ index abc..def 100644
         assert_eq!(commits[0].short_hash, "abc123");
         assert_eq!(commits[0].author_name, "John Doe");
         assert_eq!(commits[0].author_email, "john@example.com");
-        assert_eq!(commits[0].timestamp, 1700000000);
+        assert_eq!(commits[0].commit_timestamp, 1700000000);
         assert_eq!(commits[0].summary, "Fix bug");
         assert_eq!(commits[0].message, "Detailed description");
     }
@@ -0,0 +1,321 @@
+use crate::claude_status::ClaudeSession;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Aggregated Claude usage for one project on one day
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionHistoryDay {
+    pub date: String, // YYYY-MM-DD, local time
+    pub project_path: String,
+    pub session_count: u32,
+    pub completed_count: u32,
+    pub total_duration_secs: i64,
+}
+
+/// Get the path to the history database (~/.config/woodeye/history.db)
+pub fn get_history_db_path() -> Result<PathBuf, String> {
+    Ok(crate::config::home_dir()?
+        .join(".config")
+        .join("woodeye")
+        .join("history.db"))
+}
+
+/// Open the history database, creating it and its tables if needed
+fn open_db() -> Result<Connection, String> {
+    let db_path = get_history_db_path()?;
+
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+    }
+
+    let conn = Connection::open(&db_path)
+        .map_err(|e| format!("Failed to open history database: {}", e))?;
+    init_db(&conn)?;
+    Ok(conn)
+}
+
+/// Create the history tables if they don't exist yet
+fn init_db(conn: &Connection) -> Result<(), String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            session_id TEXT PRIMARY KEY,
+            project_path TEXT NOT NULL,
+            started_at INTEGER NOT NULL,
+            ended_at INTEGER,
+            duration_secs INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS transitions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            state TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_sessions_started_at ON sessions(started_at);",
+    )
+    .map_err(|e| format!("Failed to initialize history database: {}", e))
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Records session lifecycle transitions by diffing successive status snapshots
+pub struct SessionTracker {
+    conn: Connection,
+    known: HashMap<String, ClaudeSession>,
+}
+
+impl SessionTracker {
+    /// Create a tracker seeded with the sessions that already exist, so that
+    /// sessions started while Woodeye was closed are not reported as new transitions
+    pub fn new(initial: &[ClaudeSession]) -> Result<Self, String> {
+        Self::with_connection(open_db()?, initial)
+    }
+
+    fn with_connection(conn: Connection, initial: &[ClaudeSession]) -> Result<Self, String> {
+        let mut tracker = Self {
+            conn,
+            known: HashMap::new(),
+        };
+
+        for session in initial {
+            tracker.ensure_session_row(session)?;
+            tracker
+                .known
+                .insert(session.session_id.clone(), session.clone());
+        }
+
+        Ok(tracker)
+    }
+
+    /// Compare the current sessions against the last snapshot and persist any changes
+    pub fn record(&mut self, sessions: &[ClaudeSession]) -> Result<(), String> {
+        let mut current: HashMap<String, ClaudeSession> = HashMap::new();
+
+        for session in sessions {
+            let previous_state = self
+                .known
+                .get(&session.session_id)
+                .map(|s| s.state.as_str());
+
+            if previous_state != Some(session.state.as_str()) {
+                self.ensure_session_row(session)?;
+                self.insert_transition(
+                    &session.session_id,
+                    &session.project_path,
+                    &session.state,
+                    session.timestamp as i64,
+                )?;
+
+                // Stop hook: a working/waiting session went back to idle
+                if previous_state.is_some() && session.state == "idle" {
+                    self.finish_session(&session.session_id, session.timestamp as i64)?;
+                }
+            }
+
+            current.insert(session.session_id.clone(), session.clone());
+        }
+
+        // SessionEnd hook: the status file was removed
+        let ended: Vec<ClaudeSession> = self
+            .known
+            .values()
+            .filter(|s| !current.contains_key(&s.session_id))
+            .cloned()
+            .collect();
+
+        for session in ended {
+            let now = now_secs();
+            self.insert_transition(&session.session_id, &session.project_path, "ended", now)?;
+            self.finish_session(&session.session_id, now)?;
+        }
+
+        self.known = current;
+        Ok(())
+    }
+
+    fn ensure_session_row(&self, session: &ClaudeSession) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO sessions (session_id, project_path, started_at)
+                 VALUES (?1, ?2, ?3)
+                 ON CONFLICT(session_id) DO NOTHING",
+                params![
+                    session.session_id,
+                    session.project_path,
+                    session.timestamp as i64
+                ],
+            )
+            .map_err(|e| format!("Failed to record session: {}", e))?;
+        Ok(())
+    }
+
+    fn insert_transition(
+        &self,
+        session_id: &str,
+        project_path: &str,
+        state: &str,
+        timestamp: i64,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO transitions (session_id, project_path, state, timestamp)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, project_path, state, timestamp],
+            )
+            .map_err(|e| format!("Failed to record transition: {}", e))?;
+        Ok(())
+    }
+
+    /// Update a session's end time and duration (later Stop/SessionEnd events overwrite earlier ones)
+    fn finish_session(&self, session_id: &str, ended_at: i64) -> Result<(), String> {
+        self.conn
+            .execute(
+                "UPDATE sessions
+                 SET ended_at = ?2, duration_secs = MAX(0, ?2 - started_at)
+                 WHERE session_id = ?1",
+                params![session_id, ended_at],
+            )
+            .map_err(|e| format!("Failed to finish session: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Get per-day, per-project session stats for sessions started in [since, until)
+pub fn get_session_history(since: i64, until: i64) -> Result<Vec<SessionHistoryDay>, String> {
+    let conn = open_db()?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT date(started_at, 'unixepoch', 'localtime') AS day,
+                    project_path,
+                    COUNT(*),
+                    COUNT(ended_at),
+                    COALESCE(SUM(duration_secs), 0)
+             FROM sessions
+             WHERE started_at >= ?1 AND started_at < ?2
+             GROUP BY day, project_path
+             ORDER BY day, project_path",
+        )
+        .map_err(|e| format!("Failed to query session history: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![since, until], |row| {
+            Ok(SessionHistoryDay {
+                date: row.get(0)?,
+                project_path: row.get(1)?,
+                session_count: row.get(2)?,
+                completed_count: row.get(3)?,
+                total_duration_secs: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query session history: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read session history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, state: &str, timestamp: u64) -> ClaudeSession {
+        ClaudeSession {
+            schema: crate::claude_status::STATUS_SCHEMA_VERSION,
+            project_path: "/src/repo".to_string(),
+            session_id: id.to_string(),
+            state: state.to_string(),
+            timestamp,
+            name: None,
+            raw_json: String::new(),
+            stale: false,
+        }
+    }
+
+    fn tracker(initial: &[ClaudeSession]) -> SessionTracker {
+        let conn = Connection::open_in_memory().unwrap();
+        init_db(&conn).unwrap();
+        SessionTracker::with_connection(conn, initial).unwrap()
+    }
+
+    fn ended(tracker: &SessionTracker, id: &str) -> (Option<i64>, Option<i64>) {
+        tracker
+            .conn
+            .query_row(
+                "SELECT ended_at, duration_secs FROM sessions WHERE session_id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_working_to_idle_finishes_session() {
+        let mut tracker = tracker(&[]);
+        tracker.record(&[session("s1", "working", 100)]).unwrap();
+        assert_eq!(ended(&tracker, "s1"), (None, None));
+
+        tracker.record(&[session("s1", "idle", 160)]).unwrap();
+        assert_eq!(ended(&tracker, "s1"), (Some(160), Some(60)));
+
+        // A later Stop overwrites the end time
+        tracker.record(&[session("s1", "working", 200)]).unwrap();
+        tracker.record(&[session("s1", "idle", 250)]).unwrap();
+        assert_eq!(ended(&tracker, "s1"), (Some(250), Some(150)));
+
+        let transitions: i64 = tracker
+            .conn
+            .query_row("SELECT COUNT(*) FROM transitions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(transitions, 4);
+    }
+
+    #[test]
+    fn test_new_idle_session_is_not_finished() {
+        let mut tracker = tracker(&[]);
+        tracker.record(&[session("s1", "idle", 100)]).unwrap();
+        assert_eq!(ended(&tracker, "s1"), (None, None));
+    }
+
+    #[test]
+    fn test_initial_sessions_are_not_transitions() {
+        let mut tracker = tracker(&[session("s1", "working", 100)]);
+        tracker.record(&[session("s1", "working", 100)]).unwrap();
+        let transitions: i64 = tracker
+            .conn
+            .query_row("SELECT COUNT(*) FROM transitions", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(transitions, 0);
+
+        tracker.record(&[session("s1", "idle", 130)]).unwrap();
+        assert_eq!(ended(&tracker, "s1"), (Some(130), Some(30)));
+    }
+
+    #[test]
+    fn test_removed_session_is_ended() {
+        let mut tracker = tracker(&[]);
+        tracker.record(&[session("s1", "working", 100)]).unwrap();
+        tracker.record(&[]).unwrap();
+
+        let (ended_at, duration) = ended(&tracker, "s1");
+        assert!(ended_at.is_some());
+        assert!(duration.unwrap() >= 0);
+        let last_state: String = tracker
+            .conn
+            .query_row(
+                "SELECT state FROM transitions ORDER BY id DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(last_state, "ended");
+    }
+}
@@ -2,52 +2,172 @@ mod claude_status;
 mod commands;
 mod config;
 mod git;
+mod history;
 mod menu;
+mod shell_env;
+mod terminal;
 mod types;
 mod watcher;
 
 pub use commands::*;
 pub use types::*;
 
+use std::sync::Mutex;
+use tauri::Manager;
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                watcher::stop_window(window.app_handle(), window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::list_worktrees,
+            commands::get_worktree,
+            commands::export_worktrees,
+            commands::import_worktrees,
+            commands::get_worktrees_dashboard,
+            commands::get_attention_items,
             commands::start_watching,
+            commands::add_watch_path,
+            commands::remove_watch_path,
+            commands::get_last_watch_error,
+            commands::start_watching_diff,
+            commands::stop_watching_diff,
             commands::get_commit_history,
+            commands::get_log_summary,
+            commands::get_identity,
+            commands::set_worktree_identity,
+            commands::stash_save,
+            commands::get_conflicts,
+            commands::mark_resolved,
+            commands::resolve_conflict,
+            commands::continue_operation,
+            commands::get_commit_template,
+            commands::commit,
+            commands::reword_head,
+            commands::squash_head,
             commands::get_commit_diff,
+            commands::get_file_diff,
+            commands::get_working_diff_for_file,
+            commands::lfs_status,
+            commands::branch_diff_from_base,
+            commands::commits_ahead_of,
+            commands::commit_activity,
+            commands::needs_rebase,
+            commands::worktree_relationships,
+            commands::get_stash_diff,
+            commands::get_file_at_commit,
+            commands::diff_summary,
+            commands::list_tree,
+            commands::apply_patch,
+            commands::export_patch,
+            commands::save_patch,
             commands::get_working_diff,
+            commands::checkout_file,
             commands::get_worktree_status,
+            commands::count_changes,
+            commands::is_worktree_clean,
             commands::create_worktree,
+            commands::validate_worktree_target,
             commands::delete_worktree,
+            commands::delete_worktrees,
+            commands::worktree_conflicts,
             commands::prune_worktrees,
             commands::list_branches,
+            commands::branches_containing,
+            commands::rename_current_branch,
+            commands::branch_exists,
+            commands::recent_branches,
+            commands::default_branch,
+            commands::pr_url,
+            commands::repo_fingerprint,
+            commands::list_remotes,
+            commands::prune_remote,
+            commands::discover_repos,
+            commands::gitdir_for_worktree,
+            commands::object_store_size,
+            commands::run_gc,
             commands::open_in_terminal,
+            commands::test_terminal,
             commands::open_claude_in_terminal,
             commands::set_theme_menu_state,
             commands::list_claude_sessions,
+            commands::get_worktree_claude_sessions,
+            commands::list_claude_projects,
+            commands::diagnose_status_files,
+            commands::get_all_claude_statuses,
             commands::delete_claude_session,
+            commands::set_session_state,
+            commands::set_session_name,
+            commands::reveal_status_dir,
+            commands::open_session_log,
             commands::start_watching_claude_status,
+            commands::get_watcher_health,
+            commands::get_session_history,
             commands::open_claude_status_window,
+            commands::open_repo_window,
+            commands::check_claude_cli,
             commands::get_claude_hooks_state,
             commands::remove_claude_hooks,
             commands::apply_claude_hooks,
+            commands::take_hooks_auto_applied,
             commands::set_claude_status_always_on_top,
+            commands::set_claude_status_window_style,
+            commands::get_claude_status_settings,
             commands::focus_terminal_for_path,
+            commands::focus_all_waiting_terminals,
             commands::get_config,
+            commands::get_effective_config,
+            commands::get_app_version,
             commands::set_custom_script_path,
             commands::run_custom_script,
+            commands::run_action,
             commands::open_config_file
         ])
         .setup(|app| {
+            shell_env::init();
             if let Err(e) = menu::build_menu(app) {
                 eprintln!("Failed to build menu: {}", e);
             }
             menu::setup_menu_events(app);
+            // No webview is listening yet, so the frontend asks for the outcome
+            let outcome = auto_apply_hooks_if_missing();
+            app.manage(commands::HooksAutoApplyState(Mutex::new(outcome)));
             Ok(())
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+/// Re-apply Claude hooks on startup when the user opted in and they are missing
+/// (e.g. after reinstalling Claude). Returns what happened, if anything.
+fn auto_apply_hooks_if_missing() -> Option<commands::HooksAutoApplied> {
+    let config = config::load_config().unwrap_or_default();
+    if !config.auto_apply_hooks {
+        return None;
+    }
+
+    match claude_status::get_hooks_state() {
+        Ok(state) if !state.hooks_enabled => {
+            match claude_status::apply_hooks(config.supported_hook_events.as_deref()) {
+                Ok(_) => {
+                    eprintln!("Claude hooks were missing; re-applied them on startup");
+                    Some(commands::HooksAutoApplied { error: None })
+                }
+                Err(e) => {
+                    eprintln!("Failed to auto-apply Claude hooks: {}", e);
+                    Some(commands::HooksAutoApplied { error: Some(e) })
+                }
+            }
+        }
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!("Failed to check Claude hooks state: {}", e);
+            None
+        }
+    }
+}
@@ -22,8 +22,12 @@ pub fn build_menu(app: &App) -> Result<(), Box<dyn std::error::Error>> {
         .item(&theme_dark)
         .build()?;
 
+    let reveal_status_dir = MenuItemBuilder::with_id("reveal_status_dir", "Reveal Status Folder")
+        .build(app)?;
+
     let view_menu = SubmenuBuilder::new(app, "View")
         .item(&theme_submenu)
+        .item(&reveal_status_dir)
         .build()?;
 
     let open_config = MenuItemBuilder::with_id("open_config", "Open Config File...")
@@ -94,6 +98,13 @@ pub fn setup_menu_events(app: &App) {
                     }
                 });
             }
+            "reveal_status_dir" => {
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::commands::reveal_status_dir().await {
+                        eprintln!("Failed to reveal status folder: {}", e);
+                    }
+                });
+            }
             _ => {}
         }
     });
@@ -0,0 +1,98 @@
+use std::ffi::OsStr;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Surrounds PATH in the shell's output so greetings or other noise printed
+/// by interactive startup files can be told apart from it
+const PATH_MARKER: &str = "__WOODEYE_PATH__";
+
+/// How long to wait for the login shell before giving up
+const SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+
+static LOGIN_SHELL_PATH: OnceLock<Option<String>> = OnceLock::new();
+
+/// Extract the PATH printed between markers from the shell's output
+fn extract_marked_path(output: &str) -> Option<String> {
+    let start = output.find(PATH_MARKER)? + PATH_MARKER.len();
+    let len = output[start..].find(PATH_MARKER)?;
+    let path = output[start..start + len].trim();
+    (!path.is_empty()).then(|| path.to_string())
+}
+
+/// Ask the user's login shell for its PATH. Returns None if $SHELL is unset,
+/// the shell fails, or it doesn't finish within SHELL_TIMEOUT.
+fn read_login_shell_path() -> Option<String> {
+    let shell = std::env::var("SHELL").ok()?;
+    let script = format!("printf '{0}%s{0}' \"$PATH\"", PATH_MARKER);
+    let mut child = Command::new(&shell)
+        .args(["-ilc", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + SHELL_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            _ => {
+                eprintln!("Timed out reading PATH from {}", shell);
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    extract_marked_path(&output)
+}
+
+/// The PATH of the user's login shell, resolved once per process. GUI apps on
+/// macOS inherit a minimal PATH that misses tools installed by Homebrew,
+/// version managers, etc.
+pub fn login_shell_path() -> Option<&'static str> {
+    LOGIN_SHELL_PATH
+        .get_or_init(read_login_shell_path)
+        .as_deref()
+}
+
+/// Resolve the login shell PATH ahead of the first spawned command
+pub fn init() {
+    std::thread::spawn(login_shell_path);
+}
+
+/// Create a Command that runs with the login shell PATH, falling back to the
+/// inherited environment when it couldn't be resolved
+pub fn command(program: impl AsRef<OsStr>) -> Command {
+    let mut command = Command::new(program);
+    if let Some(path) = login_shell_path() {
+        command.env("PATH", path);
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_marked_path() {
+        let output = format!("Welcome!\n{0}/opt/homebrew/bin:/usr/bin{0}", PATH_MARKER);
+        assert_eq!(
+            extract_marked_path(&output),
+            Some("/opt/homebrew/bin:/usr/bin".to_string())
+        );
+    }
+
+    #[test]
+    fn test_extract_marked_path_missing() {
+        assert_eq!(extract_marked_path("/usr/bin"), None);
+        assert_eq!(extract_marked_path(&format!("{0}{0}", PATH_MARKER)), None);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::shell_env;
+use std::collections::HashMap;
+
+/// Quote a string for safe use as a single argument in a POSIX shell command
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Escape a string for use inside an AppleScript string literal
+fn applescript_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Run an AppleScript without waiting for it to finish
+fn spawn_applescript(script: &str) -> Result<(), String> {
+    shell_env::command("osascript")
+        .args(["-e", script])
+        .spawn()
+        .map_err(|e| format!("Failed to open terminal: {}", e))?;
+    Ok(())
+}
+
+/// Expand a user-defined terminal command template, substituting {path}
+/// with the shell-quoted directory
+pub fn expand_terminal_template(template: &str, path: &str) -> Result<String, String> {
+    if !template.contains("{path}") {
+        return Err(format!(
+            "Terminal command template is missing a {{path}} placeholder: {}",
+            template
+        ));
+    }
+    Ok(template.replace("{path}", &shell_quote(path)))
+}
+
+/// Open a terminal in the given directory. User-defined templates take
+/// precedence over the built-in terminals. With `new_window` false, Terminal,
+/// iTerm and Warp open a tab in the front window instead; templates and
+/// Ghostty (which has no scripting interface) always open a window.
+pub fn open_terminal(
+    path: &str,
+    terminal: &str,
+    custom_commands: &HashMap<String, String>,
+    new_window: bool,
+) -> Result<(), String> {
+    if let Some(template) = custom_commands.get(terminal) {
+        let command = expand_terminal_template(template, path)?;
+        shell_env::command("sh")
+            .args(["-c", &command])
+            .spawn()
+            .map_err(|e| format!("Failed to open terminal: {}", e))?;
+        return Ok(());
+    }
+
+    if !new_window {
+        if let Some(result) = open_terminal_tab(path, terminal) {
+            return result;
+        }
+    }
+
+    let result = match terminal {
+        "terminal" => shell_env::command("open")
+            .args(["-a", "Terminal", path])
+            .spawn(),
+        "warp" => shell_env::command("open")
+            .arg(format!("warp://action/new_window?path={}", path))
+            .spawn(),
+        "iterm" => shell_env::command("open")
+            .args(["-a", "iTerm", path])
+            .spawn(),
+        "ghostty" => shell_env::command("open")
+            .args(["-a", "ghostty", path])
+            .spawn(),
+        _ => return Err(format!("Unknown terminal: {}", terminal)),
+    };
+
+    result.map_err(|e| format!("Failed to open terminal: {}", e))?;
+    Ok(())
+}
+
+/// Open a new tab in the front window of a built-in terminal.
+/// Returns None for terminals without tab support.
+fn open_terminal_tab(path: &str, terminal: &str) -> Option<Result<(), String>> {
+    let shell_cmd = format!("cd {}", shell_quote(path));
+
+    let result = match terminal {
+        // Terminal.app has no "new tab" command, so send Cmd-T and run the cd in
+        // the tab it creates (falling back to a new window when none is open)
+        "terminal" => spawn_applescript(&format!(
+            r#"tell application "Terminal"
+            activate
+            if (count of windows) is 0 then
+                do script "{0}"
+            else
+                tell application "System Events" to keystroke "t" using command down
+                delay 0.2
+                do script "{0}" in window 1
+            end if
+        end tell"#,
+            applescript_escape(&shell_cmd)
+        )),
+        "iterm" => spawn_applescript(&format!(
+            r#"tell application "iTerm"
+            activate
+            if (count of windows) is 0 then
+                create window with default profile
+            else
+                tell current window to create tab with default profile
+            end if
+            tell current session of current window
+                write text "{}"
+            end tell
+        end tell"#,
+            applescript_escape(&shell_cmd)
+        )),
+        "warp" => shell_env::command("open")
+            .arg(format!("warp://action/new_tab?path={}", path))
+            .spawn()
+            .map(|_| ())
+            .map_err(|e| format!("Failed to open terminal: {}", e)),
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+/// Open a terminal in the given directory and start `claude` there
+pub fn open_claude(path: &str, terminal: &str) -> Result<(), String> {
+    // Every branch runs the same shell command so the directory is escaped identically
+    let shell_cmd = format!("cd {} && claude", shell_quote(path));
+
+    match terminal {
+        "terminal" => {
+            let script = format!(
+                r#"tell application "Terminal"
+            do script "{}"
+            activate
+        end tell"#,
+                applescript_escape(&shell_cmd)
+            );
+            spawn_applescript(&script)
+        }
+        "iterm" => {
+            let script = format!(
+                r#"tell application "iTerm"
+            activate
+            set newWindow to (create window with default profile)
+            tell current session of newWindow
+                write text "{}"
+            end tell
+        end tell"#,
+                applescript_escape(&shell_cmd)
+            );
+            spawn_applescript(&script)
+        }
+        "warp" => {
+            // Warp only accepts an initial command through a launch configuration,
+            // which its URL scheme can open directly by path
+            let config_path = std::env::temp_dir().join("woodeye-warp-claude.yaml");
+            let yaml = format!(
+                "---\nname: Woodeye Claude\nwindows:\n  - tabs:\n      - layout:\n          cwd: {}\n          commands:\n            - exec: {}\n",
+                serde_json::to_string(path).map_err(|e| e.to_string())?,
+                serde_json::to_string(&shell_cmd).map_err(|e| e.to_string())?
+            );
+            std::fs::write(&config_path, yaml)
+                .map_err(|e| format!("Failed to write Warp launch configuration: {}", e))?;
+
+            shell_env::command("open")
+                .arg(format!("warp://launch/{}", config_path.to_string_lossy()))
+                .spawn()
+                .map_err(|e| format!("Failed to open terminal: {}", e))?;
+            Ok(())
+        }
+        "ghostty" => {
+            shell_env::command("open")
+                .args([
+                    "-na", "Ghostty", "--args", "-e", "/bin/sh", "-lc", &shell_cmd,
+                ])
+                .spawn()
+                .map_err(|e| format!("Failed to open terminal: {}", e))?;
+            Ok(())
+        }
+        _ => Err(format!("Unknown terminal: {}", terminal)),
+    }
+}
+
+/// Focus the Terminal.app tab whose shell is running in the given path.
+/// Returns false if no matching tab was found.
+pub fn focus_terminal_for_path(path: &str) -> Result<bool, String> {
+    // Step 1: Find processes with cwd matching the target path using lsof
+    let lsof_output = shell_env::command("lsof")
+        .args(["-d", "cwd"])
+        .output()
+        .map_err(|e| format!("Failed to run lsof: {}", e))?;
+
+    let lsof_str = String::from_utf8_lossy(&lsof_output.stdout);
+
+    // Step 2: Parse lsof output to find PIDs with matching cwd
+    // Format: COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+    let matching_pids: Vec<String> = lsof_str
+        .lines()
+        .filter(|line| line.ends_with(path) || line.contains(&format!("{} ", path)))
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() >= 2 {
+                Some(parts[1].to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // Step 3: For each PID, get its tty
+    for pid in matching_pids {
+        let ps_output = shell_env::command("ps")
+            .args(["-p", &pid, "-o", "tty="])
+            .output()
+            .map_err(|e| format!("Failed to run ps: {}", e))?;
+
+        let tty = String::from_utf8_lossy(&ps_output.stdout).trim().to_string();
+
+        // Skip if no tty (e.g., "??") or empty
+        if tty.is_empty() || tty == "??" {
+            continue;
+        }
+
+        // Step 4: Try to focus Terminal tab with this tty
+        let tty_path = format!("/dev/{}", tty);
+        let script = format!(
+            r#"tell application "System Events"
+    if not (exists process "Terminal") then
+        return false
+    end if
+end tell
+
+tell application "Terminal"
+    repeat with w in windows
+        repeat with t in tabs of w
+            if tty of t is "{tty_path}" then
+                set frontmost of w to true
+                set selected of t to true
+                activate
+                return true
+            end if
+        end repeat
+    end repeat
+    return false
+end tell"#,
+            tty_path = tty_path.replace("\"", "\\\"")
+        );
+
+        let output = shell_env::command("osascript")
+            .args(["-e", &script])
+            .output()
+            .map_err(|e| format!("Failed to run AppleScript: {}", e))?;
+
+        let result = String::from_utf8_lossy(&output.stdout).trim().to_lowercase();
+        if result == "true" {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/tmp/repo"), "'/tmp/repo'");
+        assert_eq!(shell_quote("/tmp/it's here"), "'/tmp/it'\\''s here'");
+    }
+
+    #[test]
+    fn test_expand_terminal_template() {
+        assert_eq!(
+            expand_terminal_template("wezterm start --cwd {path}", "/tmp/my repo").unwrap(),
+            "wezterm start --cwd '/tmp/my repo'"
+        );
+    }
+
+    #[test]
+    fn test_expand_terminal_template_requires_placeholder() {
+        assert!(expand_terminal_template("wezterm start", "/tmp").is_err());
+    }
+}
@@ -10,6 +10,14 @@ pub struct Worktree {
     /// Status is optional for lazy loading - initially None, fetched separately
     pub status: Option<WorktreeStatus>,
     pub last_commit_timestamp: i64,
+    /// Newest of the HEAD commit date and the worktree directory's mtime, for sorting by activity
+    pub last_modified: i64,
+    pub is_bare: bool,
+    pub is_detached: bool,
+    /// Locked against pruning (`git worktree lock`)
+    pub is_locked: bool,
+    /// False when the directory is gone but git still has a record of it
+    pub exists_on_disk: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +42,24 @@ pub struct WorktreeStatus {
     pub staged: u32,
     pub untracked: u32,
     pub conflicted: u32,
+    /// Rebase/merge/cherry-pick/revert left in progress, if any
+    pub operation: Option<OperationProgress>,
+}
+
+/// Lightweight change counts for frequently-refreshed badges
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeCounts {
+    pub staged: u32,
+    pub unstaged: u32,
+    pub untracked: u32,
+}
+
+/// Whether a worktree is safe to switch, reset or delete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CleanState {
+    pub clean: bool,
+    /// Human-readable reasons it isn't clean, empty when clean
+    pub reasons: Vec<String>,
 }
 
 // Commit history types
@@ -43,11 +69,22 @@ pub struct CommitInfo {
     pub short_hash: String,
     pub author_name: String,
     pub author_email: String,
-    pub timestamp: i64,
+    /// Committer date (%ct) in unix seconds, UTC
+    pub commit_timestamp: i64,
     pub message: String,
     pub summary: String,
 }
 
+/// Commits made on one day, with their combined line changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayActivity {
+    /// YYYY-MM-DD, local time
+    pub date: String,
+    pub commits: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitDiff {
     pub commit: CommitInfo,
@@ -62,6 +99,19 @@ pub struct FileDiff {
     pub old_path: Option<String>,
     pub hunks: Vec<DiffHunk>,
     pub binary: bool,
+    /// Hunks were left out because the file or its diff is too large
+    pub truncated: bool,
+    /// Managed by Git LFS; hunks are left out as they'd only show the pointer
+    pub is_lfs: bool,
+}
+
+/// A file tracked by Git LFS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LfsFile {
+    pub path: String,
+    pub oid: String,
+    /// False when only the pointer file is checked out
+    pub downloaded: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,15 +153,149 @@ pub struct WorkingDiff {
     pub stats: DiffStats,
 }
 
+/// A file or directory directly inside a listed worktree directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TreeEntry {
+    /// Path relative to the worktree root
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// A file's contents at a given commit. Binary files have no content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileBlob {
+    pub content: Option<String>,
+    pub is_binary: bool,
+}
+
+/// A file in a patch that `git apply` couldn't apply
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatchConflict {
+    pub path: String,
+    /// Start line of the first hunk that didn't match, when git reports it
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// Outcome of applying a patch. Patches apply atomically: when there are
+/// conflicts, no file is changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyResult {
+    pub applied: bool,
+    /// Files whose hunks all apply cleanly
+    pub clean_files: Vec<String>,
+    pub conflicts: Vec<PatchConflict>,
+}
+
 // Worktree management types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateWorktreeOptions {
-    pub path: String,
+    /// Target directory. When omitted, the path is derived from the configured
+    /// worktree base directory as <base>/<repo-name>/<branch>.
+    pub path: Option<String>,
     pub new_branch: Option<String>,
     pub commit_ish: Option<String>,
     pub detach: bool,
 }
 
+/// Why a path can't be used as a new worktree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WorktreeTargetIssue {
+    ParentMissing,
+    ParentNotWritable,
+    NotADirectory,
+    PathNotEmpty,
+    AlreadyWorktree,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationResult {
+    pub valid: bool,
+    pub issue: Option<WorktreeTargetIssue>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteWorktreeResult {
+    /// The worktree's branch, if it was deleted along with it
+    pub deleted_branch: Option<String>,
+    /// Why the branch was kept when its deletion was requested
+    pub kept_branch_reason: Option<String>,
+}
+
+/// Outcome of deleting one worktree of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteOutcome {
+    pub path: String,
+    /// Set when the worktree was deleted
+    pub result: Option<DeleteWorktreeResult>,
+    /// Why it wasn't, e.g. uncommitted changes without `force`
+    pub error: Option<String>,
+}
+
+/// Outcome of recreating one worktree of an imported layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOutcome {
+    pub path: String,
+    pub branch: Option<String>,
+    /// Set when the worktree was created
+    pub worktree: Option<Worktree>,
+    /// The worktree already exists, so nothing was done
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WorktreeConflictKind {
+    /// The same branch is checked out in more than one worktree
+    DuplicateBranch,
+    /// The worktree's directory or its link to the repository is missing
+    MissingGitdir,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeConflict {
+    pub kind: WorktreeConflictKind,
+    pub branch: Option<String>,
+    pub paths: Vec<PathBuf>,
+}
+
+/// How a worktree's HEAD relates to the default branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeRelation {
+    pub path: PathBuf,
+    /// None when detached
+    pub branch: Option<String>,
+    /// None when HEAD shares no history with the default branch
+    pub merge_base: Option<String>,
+    /// Commits on HEAD that aren't on the default branch
+    pub ahead: u32,
+    /// Commits on the default branch that aren't on HEAD
+    pub behind: u32,
+}
+
+/// Whether a branch has fallen behind its base
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseAdvice {
+    /// The base compared against, e.g. "origin/main"
+    pub base: String,
+    /// Commits on the base that aren't on HEAD
+    pub behind: u32,
+    /// The base has advanced past the merge base, so a rebase is advised
+    pub base_moved: bool,
+}
+
+/// Snapshot of a repository's worktree layout, as written by export_worktrees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorktreeExport {
+    pub repo_path: PathBuf,
+    /// Default branch of origin, when known
+    pub default_branch: Option<String>,
+    pub remotes: Vec<Remote>,
+    /// With their status, except for worktrees whose directory is missing
+    pub worktrees: Vec<Worktree>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PruneResult {
     pub pruned_count: u32,
@@ -124,3 +308,106 @@ pub struct BranchInfo {
     pub is_remote: bool,
     pub is_checked_out: bool,
 }
+
+/// Branches whose history includes a given commit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BranchesContaining {
+    pub local: Vec<String>,
+    /// Remote-tracking branches, e.g. "origin/main"
+    pub remote: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchExistence {
+    pub local: bool,
+    /// The first remote that has the branch, if any
+    pub remote: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Remote {
+    pub name: String,
+    pub fetch_url: String,
+    pub push_url: String,
+}
+
+/// The identity commits will be attributed to. Unset values are None.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Identity {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// A multi-step git operation that can be left in progress (e.g. by a conflict)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GitOperation {
+    Rebase,
+    Merge,
+    CherryPick,
+    Revert,
+}
+
+/// An in-progress operation and, for rebases, how far through it is.
+/// The counts are None when git doesn't record them (e.g. `rebase --apply`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationProgress {
+    pub operation: GitOperation,
+    pub current: Option<u32>,
+    pub total: Option<u32>,
+}
+
+/// A conflicted region of a file, between `<<<<<<<` and `>>>>>>>` markers.
+/// Sections keep their line endings, so joining one back in place of the
+/// markers resolves the hunk.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConflictHunk {
+    /// 1-based line of the `<<<<<<<` marker
+    pub start_line: u32,
+    /// 1-based line of the `>>>>>>>` marker
+    pub end_line: u32,
+    /// Text after the `<<<<<<<` marker, e.g. "HEAD"
+    pub ours_label: String,
+    /// Text after the `>>>>>>>` marker, e.g. the merged branch
+    pub theirs_label: String,
+    pub ours: String,
+    /// The common ancestor's version, only present with the diff3 or zdiff3 conflict style
+    pub base: Option<String>,
+    pub theirs: String,
+}
+
+/// A file with unresolved conflicts. Files with no markers to parse (binary
+/// files, or ones deleted on one side) have no hunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConflictFile {
+    pub path: String,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationState {
+    /// The operation still in progress, or None once it has completed
+    pub operation: Option<GitOperation>,
+    pub conflicted_files: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitDirs {
+    /// The worktree's own gitdir, holding HEAD, the index and operation state
+    pub git_dir: PathBuf,
+    /// The gitdir shared by all worktrees, holding objects, refs and config
+    pub common_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcResult {
+    /// Size of the object store in bytes before and after gc
+    pub size_before: u64,
+    pub size_after: u64,
+}
+
+/// A line of progress output from a long-running git command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitProgress {
+    pub repo_path: String,
+    pub message: String,
+}
@@ -1,48 +1,203 @@
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
-use std::path::Path;
-use std::sync::mpsc;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
 
-pub fn start_watching(app: AppHandle, paths: Vec<String>) -> Result<(), String> {
+type Debouncer = notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>;
+
+/// A window's repository watcher
+struct WindowWatcher {
+    debouncer: Debouncer,
+    /// Watched worktree roots, shared with the event thread so ignore globs
+    /// can be matched relative to the worktree an event came from
+    roots: Arc<Mutex<Vec<PathBuf>>>,
+}
+
+// State to keep the debouncers alive and allow paths to be added later. Each
+// window watches its own repository, so everything is keyed by window label.
+#[derive(Default)]
+struct WatcherState {
+    watchers: Mutex<HashMap<String, WindowWatcher>>,
+    /// Most recent error reported by a window's watcher threads, cleared when
+    /// its watcher restarts
+    last_errors: Mutex<HashMap<String, WatchError>>,
+}
+
+/// An error a watcher thread hit while running, also emitted as `watch-error`
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchError {
+    pub message: String,
+    /// The path the error concerns, if notify reported one
+    pub path: Option<PathBuf>,
+    /// Unix seconds
+    pub timestamp: u64,
+}
+
+fn watcher_state(app: &AppHandle) -> tauri::State<'_, WatcherState> {
+    if app.try_state::<WatcherState>().is_none() {
+        app.manage(WatcherState::default());
+    }
+    app.state::<WatcherState>()
+}
+
+/// Payload of `working-diff-changed`
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkingDiffChanged {
+    pub worktree_path: String,
+    /// Files still unmerged while a rebase, merge, cherry-pick or revert is in
+    /// progress; None without one
+    pub conflicts: Option<u32>,
+}
+
+/// Remember a watcher thread's error for get_last_watch_error and tell the
+/// window it was watching for
+fn record_watch_error(
+    app: &AppHandle,
+    label: &str,
+    error: &notify::Error,
+    fallback_path: Option<&Path>,
+) {
+    eprintln!("Watch error: {:?}", error);
+    let watch_error = WatchError {
+        message: error.to_string(),
+        path: error
+            .paths
+            .first()
+            .map(PathBuf::as_path)
+            .or(fallback_path)
+            .map(Path::to_path_buf),
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    if let Ok(mut last_errors) = watcher_state(app).last_errors.lock() {
+        last_errors.insert(label.to_string(), watch_error.clone());
+    }
+    let _ = app.emit_to(label, "watch-error", watch_error);
+}
+
+/// A window's most recent watcher thread error since its watching was last
+/// (re)started
+pub fn last_watch_error(app: &AppHandle, label: &str) -> Option<WatchError> {
+    watcher_state(app)
+        .last_errors
+        .lock()
+        .ok()
+        .and_then(|e| e.get(label).cloned())
+}
+
+/// Watch the .git directory if it exists, otherwise the path itself
+fn watch_target(path_str: &str) -> PathBuf {
+    let path = Path::new(path_str);
+    let watch_path = path.join(".git");
+    if watch_path.exists() {
+        watch_path
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn watch(debouncer: &mut Debouncer, path_str: &str) -> Result<(), String> {
+    let target = watch_target(path_str);
+    debouncer
+        .watcher()
+        .watch(&target, notify::RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", target.display(), e))
+}
+
+/// The root ignore globs are matched against. Event paths are reported
+/// canonicalized on some platforms, so the root is too.
+fn canonical_root(path_str: &str) -> PathBuf {
+    let path = Path::new(path_str);
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Compile ignore glob patterns into a single matcher
+fn build_ignore_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).map_err(|e| format!("Invalid ignore glob {}: {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// Load the configured ignore globs, falling back to the defaults if the
+/// config can't be read or contains an invalid pattern
+fn load_ignore_set() -> GlobSet {
+    let patterns = crate::config::load_config()
+        .map(|c| c.ignore_globs)
+        .unwrap_or_else(|_| crate::config::default_ignore_globs());
+    build_ignore_set(&patterns).unwrap_or_else(|e| {
+        eprintln!("{}, using default ignore globs", e);
+        build_ignore_set(&crate::config::default_ignore_globs())
+            .unwrap_or_else(|_| GlobSet::empty())
+    })
+}
+
+/// Whether a changed path matches an ignore glob, relative to the deepest
+/// watched root containing it. Paths outside every root are never ignored.
+fn is_ignored(ignore: &GlobSet, roots: &[PathBuf], path: &Path) -> bool {
+    roots
+        .iter()
+        .filter_map(|root| path.strip_prefix(root).ok())
+        .min_by_key(|relative| relative.components().count())
+        .is_some_and(|relative| ignore.is_match(relative))
+}
+
+/// Watch `paths` for a window, replacing that window's previous watcher.
+/// Changes are emitted as `worktree-changed` to that window only.
+pub fn start_watching(app: AppHandle, label: String, paths: Vec<String>) -> Result<(), String> {
     let (tx, rx) = mpsc::channel();
 
     let mut debouncer = new_debouncer(Duration::from_millis(200), tx).map_err(|e| e.to_string())?;
 
     for path_str in &paths {
-        let path = Path::new(path_str);
-        // Watch the .git directory if it exists, otherwise the path itself
-        let watch_path = path.join(".git");
-        let target = if watch_path.exists() {
-            watch_path
-        } else {
-            path.to_path_buf()
-        };
-
-        debouncer
-            .watcher()
-            .watch(&target, notify::RecursiveMode::Recursive)
-            .map_err(|e| format!("Failed to watch {}: {}", target.display(), e))?;
+        watch(&mut debouncer, path_str)?;
     }
 
-    // Store the debouncer in app state to keep it alive
-    app.manage(WatcherState { _debouncer: debouncer });
+    // Store the debouncer in app state to keep it alive, replacing any previous one
+    let roots = Arc::new(Mutex::new(
+        paths.iter().map(|p| canonical_root(p)).collect(),
+    ));
+    let state = watcher_state(&app);
+    state.watchers.lock().map_err(|e| e.to_string())?.insert(
+        label.clone(),
+        WindowWatcher {
+            debouncer,
+            roots: Arc::clone(&roots),
+        },
+    );
+    state
+        .last_errors
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&label);
 
-    // Spawn thread to handle events
+    // Spawn thread to handle events (it exits when its debouncer is dropped)
     let app_handle = app.clone();
+    let ignore = load_ignore_set();
     std::thread::spawn(move || {
         while let Ok(result) = rx.recv() {
             match result {
                 Ok(events) => {
-                    // Only emit if there are actual changes
-                    let has_changes = events
-                        .iter()
-                        .any(|e| matches!(e.kind, DebouncedEventKind::Any));
+                    // Only emit if there are actual changes outside ignored paths
+                    let roots = roots.lock().map(|r| r.clone()).unwrap_or_default();
+                    let has_changes = events.iter().any(|e| {
+                        matches!(e.kind, DebouncedEventKind::Any)
+                            && !is_ignored(&ignore, &roots, &e.path)
+                    });
                     if has_changes {
-                        let _ = app_handle.emit("worktree-changed", ());
+                        let _ = app_handle.emit_to(label.as_str(), "worktree-changed", ());
                     }
                 }
-                Err(e) => eprintln!("Watch error: {:?}", e),
+                Err(e) => record_watch_error(&app_handle, &label, &e, None),
             }
         }
     });
@@ -50,7 +205,208 @@ pub fn start_watching(app: AppHandle, paths: Vec<String>) -> Result<(), String>
     Ok(())
 }
 
-// State to keep the debouncer alive
-struct WatcherState {
-    _debouncer: notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>,
+/// Add a path to a window's running watcher, starting one if that window
+/// hasn't begun watching yet
+pub fn add_watch_path(app: AppHandle, label: String, path: String) -> Result<(), String> {
+    let added = {
+        let state = watcher_state(&app);
+        let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+        match watchers.get_mut(&label) {
+            Some(watcher) => {
+                watch(&mut watcher.debouncer, &path)?;
+                watcher
+                    .roots
+                    .lock()
+                    .map_err(|e| e.to_string())?
+                    .push(canonical_root(&path));
+                true
+            }
+            None => false,
+        }
+    };
+
+    if added {
+        Ok(())
+    } else {
+        start_watching(app, label, vec![path])
+    }
+}
+
+/// Stop watching a path in a window. Paths that were never watched (or a
+/// window that hasn't started watching) are ignored.
+pub fn remove_watch_path(app: AppHandle, label: String, path: String) -> Result<(), String> {
+    let state = watcher_state(&app);
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    match watchers.get_mut(&label) {
+        Some(watcher) => unwatch(watcher, &path),
+        None => Ok(()),
+    }
+}
+
+/// Stop watching a path in every window, e.g. once the worktree is deleted
+pub fn remove_watch_path_everywhere(app: AppHandle, path: String) -> Result<(), String> {
+    let state = watcher_state(&app);
+    let mut watchers = state.watchers.lock().map_err(|e| e.to_string())?;
+    watchers
+        .values_mut()
+        .try_for_each(|watcher| unwatch(watcher, &path))
+}
+
+fn unwatch(watcher: &mut WindowWatcher, path: &str) -> Result<(), String> {
+    let root = canonical_root(path);
+    watcher
+        .roots
+        .lock()
+        .map_err(|e| e.to_string())?
+        .retain(|r| *r != root);
+    // The .git target may already be gone if the worktree was deleted, so try
+    // both candidates rather than re-deriving it with watch_target
+    let path = Path::new(path);
+    for target in [path.join(".git"), path.to_path_buf()] {
+        match watcher.debouncer.watcher().unwatch(&target) {
+            Ok(())
+            | Err(notify::Error {
+                kind: notify::ErrorKind::WatchNotFound,
+                ..
+            }) => {}
+            Err(e) => return Err(format!("Failed to unwatch {}: {}", target.display(), e)),
+        }
+    }
+    Ok(())
+}
+
+/// Drop a window's watchers and their last error, once the window is gone
+pub fn stop_window(app: &AppHandle, label: &str) {
+    // Dropping a debouncer closes its channel, which ends its event thread
+    let state = watcher_state(app);
+    if let Ok(mut watchers) = state.watchers.lock() {
+        watchers.remove(label);
+    }
+    if let Ok(mut last_errors) = state.last_errors.lock() {
+        last_errors.remove(label);
+    }
+    let diff_state = diff_watcher_state(app);
+    if let Ok(mut debouncers) = diff_state.debouncers.lock() {
+        debouncers.retain(|(window, _), _| window != label);
+    };
+}
+
+// Per-worktree watchers for live diff panels, keyed by window label and
+// worktree path
+#[derive(Default)]
+struct DiffWatcherState {
+    debouncers: Mutex<HashMap<(String, String), Debouncer>>,
+}
+
+fn diff_watcher_state(app: &AppHandle) -> tauri::State<'_, DiffWatcherState> {
+    if app.try_state::<DiffWatcherState>().is_none() {
+        app.manage(DiffWatcherState::default());
+    }
+    app.state::<DiffWatcherState>()
+}
+
+/// Watch a worktree for a window and emit `working-diff-changed` to it with the
+/// worktree's path and conflict count whenever a file that could affect its diff
+/// changes. Watching an already watched worktree is a no-op.
+pub fn start_watching_diff(
+    app: AppHandle,
+    label: String,
+    worktree_path: String,
+) -> Result<(), String> {
+    let state = diff_watcher_state(&app);
+    let mut debouncers = state.debouncers.lock().map_err(|e| e.to_string())?;
+    let key = (label, worktree_path);
+    if debouncers.contains_key(&key) {
+        return Ok(());
+    }
+    let (label, worktree_path) = key.clone();
+
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer = new_debouncer(Duration::from_millis(200), tx).map_err(|e| e.to_string())?;
+
+    debouncer
+        .watcher()
+        .watch(Path::new(&worktree_path), notify::RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", worktree_path, e))?;
+
+    let roots = vec![canonical_root(&worktree_path)];
+    let ignore = load_ignore_set();
+    let app_handle = app.clone();
+    let path = worktree_path.clone();
+    std::thread::spawn(move || {
+        while let Ok(result) = rx.recv() {
+            match result {
+                Ok(events) => {
+                    let relevant = events.iter().any(|e| {
+                        matches!(e.kind, DebouncedEventKind::Any)
+                            && !is_ignored(&ignore, &roots, &e.path)
+                    });
+                    if relevant {
+                        let conflicts = crate::git::operation_conflict_count(&path).ok().flatten();
+                        let payload = WorkingDiffChanged {
+                            worktree_path: path.clone(),
+                            conflicts,
+                        };
+                        let _ = app_handle.emit_to(label.as_str(), "working-diff-changed", payload);
+                    }
+                }
+                Err(e) => record_watch_error(&app_handle, &label, &e, Some(Path::new(&path))),
+            }
+        }
+    });
+
+    debouncers.insert(key, debouncer);
+    Ok(())
+}
+
+/// Stop a window's live diff watcher for a worktree. Unwatched paths are ignored.
+pub fn stop_watching_diff(
+    app: AppHandle,
+    label: String,
+    worktree_path: String,
+) -> Result<(), String> {
+    let state = diff_watcher_state(&app);
+    // Dropping the debouncer closes its channel, which ends the event thread
+    state
+        .debouncers
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&(label, worktree_path));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ignored_default_globs() {
+        let ignore = build_ignore_set(&crate::config::default_ignore_globs()).unwrap();
+        let roots = vec![PathBuf::from("/repo")];
+        let ignored = |path: &str| is_ignored(&ignore, &roots, Path::new(path));
+        assert!(ignored("/repo/.git/objects/ab/cdef"));
+        assert!(ignored("/repo/web/node_modules/x/index.js"));
+        assert!(ignored("/repo/target/debug/app"));
+        assert!(!ignored("/repo/src/main.rs"));
+        assert!(!ignored("/repo/.git/index"));
+        assert!(!ignored("/elsewhere/target/debug/app"));
+
+        // Only components inside the worktree count
+        let roots = vec![PathBuf::from("/target/repo")];
+        assert!(!is_ignored(&ignore, &roots, Path::new("/target/repo/a.rs")));
+    }
+
+    #[test]
+    fn test_is_ignored_uses_deepest_root() {
+        let ignore = build_ignore_set(&["build/**".to_string()]).unwrap();
+        let roots = vec![PathBuf::from("/repo"), PathBuf::from("/repo/build/wt")];
+        let ignored = |path: &str| is_ignored(&ignore, &roots, Path::new(path));
+        assert!(!ignored("/repo/build/wt/src/a.rs"));
+        assert!(ignored("/repo/build/out.o"));
+    }
+
+    #[test]
+    fn test_build_ignore_set_rejects_invalid_glob() {
+        assert!(build_ignore_set(&["src/[".to_string()]).is_err());
+    }
 }
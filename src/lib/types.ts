@@ -6,6 +6,12 @@ export interface Worktree {
   /** Status is optional for lazy loading - initially null, fetched separately */
   status: WorktreeStatus | null;
   last_commit_timestamp: number;
+  last_modified: number;
+  is_bare: boolean;
+  is_detached: boolean;
+  is_locked: boolean;
+  /** False when the directory is gone but git still has a record of it */
+  exists_on_disk: boolean;
 }
 
 export interface HeadInfo {
@@ -27,6 +33,58 @@ export interface WorktreeStatus {
   staged: number;
   untracked: number;
   conflicted: number;
+  operation: OperationProgress | null;
+}
+
+export interface ChangeCounts {
+  staged: number;
+  unstaged: number;
+  untracked: number;
+}
+
+export type GitOperation = "Rebase" | "Merge" | "CherryPick" | "Revert";
+
+export interface OperationProgress {
+  operation: GitOperation;
+  current: number | null;
+  total: number | null;
+}
+
+export interface OperationState {
+  operation: GitOperation | null;
+  conflicted_files: string[];
+}
+
+export interface ConflictHunk {
+  start_line: number;
+  end_line: number;
+  ours_label: string;
+  theirs_label: string;
+  ours: string;
+  /** Only present with the diff3 or zdiff3 conflict style */
+  base: string | null;
+  theirs: string;
+}
+
+export interface ConflictFile {
+  path: string;
+  hunks: ConflictHunk[];
+}
+
+export interface Remote {
+  name: string;
+  fetch_url: string;
+  push_url: string;
+}
+
+export interface Identity {
+  name: string | null;
+  email: string | null;
+}
+
+export interface CleanState {
+  clean: boolean;
+  reasons: string[];
 }
 
 // Commit history types
@@ -35,11 +93,20 @@ export interface CommitInfo {
   short_hash: string;
   author_name: string;
   author_email: string;
-  timestamp: number;
+  /** Committer date in unix seconds, UTC */
+  commit_timestamp: number;
   message: string;
   summary: string;
 }
 
+export interface DayActivity {
+  /** YYYY-MM-DD, local time */
+  date: string;
+  commits: number;
+  insertions: number;
+  deletions: number;
+}
+
 export interface CommitDiff {
   commit: CommitInfo;
   files: FileDiff[];
@@ -52,6 +119,17 @@ export interface FileDiff {
   old_path: string | null;
   hunks: DiffHunk[];
   binary: boolean;
+  /** Hunks were left out because the file or its diff is too large */
+  truncated: boolean;
+  /** Managed by Git LFS; hunks are left out as they'd only show the pointer */
+  is_lfs: boolean;
+}
+
+export interface LfsFile {
+  path: string;
+  oid: string;
+  /** False when only the pointer file is checked out */
+  downloaded: boolean;
 }
 
 export type FileStatus = "Added" | "Modified" | "Deleted" | "Renamed";
@@ -83,14 +161,105 @@ export interface WorkingDiff {
   stats: DiffStats;
 }
 
+export interface TreeEntry {
+  /** Relative to the worktree root */
+  path: string;
+  is_dir: boolean;
+}
+
+export interface FileBlob {
+  content: string | null;
+  is_binary: boolean;
+}
+
+export interface PatchConflict {
+  path: string;
+  line: number | null;
+  message: string;
+}
+
+export interface ApplyResult {
+  applied: boolean;
+  clean_files: string[];
+  conflicts: PatchConflict[];
+}
+
 // Worktree management types
 export interface CreateWorktreeOptions {
-  path: string;
+  /** When null, derived from worktree_base_dir as <base>/<repo-name>/<branch> */
+  path: string | null;
   new_branch: string | null;
   commit_ish: string | null;
   detach: boolean;
 }
 
+export type WorktreeTargetIssue =
+  | "ParentMissing"
+  | "ParentNotWritable"
+  | "NotADirectory"
+  | "PathNotEmpty"
+  | "AlreadyWorktree";
+
+export interface ValidationResult {
+  valid: boolean;
+  issue: WorktreeTargetIssue | null;
+  message: string | null;
+}
+
+export interface DeleteWorktreeResult {
+  deleted_branch: string | null;
+  kept_branch_reason: string | null;
+}
+
+export interface DeleteOutcome {
+  path: string;
+  result: DeleteWorktreeResult | null;
+  error: string | null;
+}
+
+export interface CreateOutcome {
+  path: string;
+  branch: string | null;
+  worktree: Worktree | null;
+  /** The worktree already exists, so nothing was done */
+  skipped: boolean;
+  error: string | null;
+}
+
+export type WorktreeConflictKind = "DuplicateBranch" | "MissingGitdir";
+
+export interface WorktreeConflict {
+  kind: WorktreeConflictKind;
+  branch: string | null;
+  paths: string[];
+}
+
+/** How a worktree's HEAD relates to the default branch */
+export interface WorktreeRelation {
+  path: string;
+  branch: string | null;
+  /** Null when HEAD shares no history with the default branch */
+  merge_base: string | null;
+  ahead: number;
+  behind: number;
+}
+
+export interface RebaseAdvice {
+  /** e.g. "origin/main" */
+  base: string;
+  behind: number;
+  /** The base has advanced past the merge base, so a rebase is advised */
+  base_moved: boolean;
+}
+
+/** Snapshot of a repository's worktree layout, from export_worktrees */
+export interface WorktreeExport {
+  repo_path: string;
+  default_branch: string | null;
+  remotes: Remote[];
+  worktrees: Worktree[];
+}
+
 export interface PruneResult {
   pruned_count: number;
   messages: string[];
@@ -102,14 +271,83 @@ export interface BranchInfo {
   is_checked_out: boolean;
 }
 
+export interface BranchesContaining {
+  local: string[];
+  remote: string[];
+}
+
+export interface BranchExistence {
+  local: boolean;
+  remote: string | null;
+}
+
 // Claude session types
 export interface ClaudeSession {
+  schema: number;
   project_path: string;
   session_id: string;
   state: string; // "working", "idle", "waiting_for_approval"
   timestamp: number;
   name?: string; // Extracted from first prompt
   raw_json: string;
+  stale: boolean;
+}
+
+export interface ClaudeProject {
+  path: string;
+  /** Unix seconds of the most recent transcript write */
+  last_activity: number;
+  session_count: number;
+}
+
+export type StatusFileKind =
+  | "SessionId"
+  | "HashNamed"
+  | "Names"
+  | "HooksBackup"
+  | "Temporary"
+  | "Other";
+
+export interface StatusFileDiagnosis {
+  name: string;
+  kind: StatusFileKind;
+  /** The function that picks the file up, null when it's ignored */
+  reader: string | null;
+  session_id: string | null;
+  error: string | null;
+}
+
+export interface StatusDiagnosis {
+  status_dir: string;
+  files: StatusFileDiagnosis[];
+}
+
+export interface WorktreeClaudeStatus {
+  sessions: ClaudeSession[];
+  waiting: boolean;
+}
+
+export interface WorktreeRow {
+  worktree: Worktree;
+  status: WorktreeStatus | null;
+  claude: WorktreeClaudeStatus | null;
+  error: string | null;
+}
+
+export interface ApplyHooksResult {
+  skipped_events: string[];
+}
+
+/** Claude hooks were found missing on startup and re-applied */
+export interface HooksAutoApplied {
+  /** Set when re-applying them failed */
+  error: string | null;
+}
+
+export interface ClaudeCliInfo {
+  installed: boolean;
+  version: string | null;
+  path: string | null;
 }
 
 export interface HooksState {
@@ -117,9 +355,131 @@ export interface HooksState {
   hooks_json: string | null;
 }
 
+export type WindowLevel = "Normal" | "Floating" | "AllWorkspaces";
+
+export interface ClaudeStatusSettings {
+  always_on_top: boolean;
+  level: WindowLevel;
+  /** Applied to the window's contents, at least 0.2 */
+  opacity: number;
+}
+
+export interface WatcherHealth {
+  running: boolean;
+  started_at: number | null;
+  last_event_at: number | null;
+}
+
+/** Payload of the working-diff-changed event */
+export interface WorkingDiffChanged {
+  worktree_path: string;
+  /** Unmerged files while an operation is in progress, null without one */
+  conflicts: number | null;
+}
+
+export interface WatchError {
+  message: string;
+  path: string | null;
+  /** Unix seconds */
+  timestamp: number;
+}
+
+export type AttentionSeverity = "Info" | "Warning" | "Critical";
+
+export type AttentionKind = "Conflicts" | "ClaudeWaiting" | "Unpushed" | "Error";
+
+/** Something in a worktree that needs the user's attention */
+export interface AttentionItem {
+  kind: AttentionKind;
+  severity: AttentionSeverity;
+  worktree_path: string;
+  message: string;
+  /** Id of the action that deals with it, e.g. "push" */
+  action: string | null;
+  /** The Claude session, for ClaudeWaiting */
+  session_id: string | null;
+}
+
+export interface AppVersion {
+  version: string;
+  commit: string | null;
+  build_date: string | null;
+}
+
+export interface FocusResult {
+  session_id: string;
+  project_path: string;
+  focused: boolean;
+  error: string | null;
+}
+
+export interface SessionHistoryDay {
+  date: string; // YYYY-MM-DD, local time
+  project_path: string;
+  session_count: number;
+  completed_count: number;
+  total_duration_secs: number;
+}
+
 // Woodeye config types
 export interface WoodeyeConfig {
   custom_script_path: string | null;
+  history_enabled: boolean;
+  terminal_commands: Record<string, string>;
+  worktree_base_dir: string | null;
+  auto_apply_hooks: boolean;
+  stale_thresholds: Record<string, number>;
+  supported_hook_events: string[] | null;
+  ignore_globs: string[];
+  claude_status_always_on_top: boolean;
+  claude_status_level: WindowLevel;
+  claude_status_opacity: number;
+  actions: ActionDef[];
+  /** Actions run against every new worktree once it has been created */
+  on_create_actions: ActionRef[];
+  max_diff_bytes: number;
+  /** Commit message template file, used instead of git's commit.template */
+  commit_template: string | null;
+  /** Keyed by repo fingerprint */
+  per_repo: Record<string, RepoOverrides>;
+}
+
+export interface RepoOverrides {
+  terminal?: string | null;
+  editor?: string | null;
+  worktree_base_dir?: string | null;
+  custom_script_path?: string | null;
+  commit_template?: string | null;
+  terminal_commands?: Record<string, string>;
+  actions?: ActionDef[];
+  /** Replaces the global on_create_actions */
+  on_create_actions?: ActionRef[] | null;
+}
+
+/** The global config merged with a repository's overrides */
+export interface EffectiveConfig {
+  terminal: string | null;
+  editor: string | null;
+  worktree_base_dir: string | null;
+  custom_script_path: string | null;
+  commit_template: string | null;
+  terminal_commands: Record<string, string>;
+  actions: ActionDef[];
+  on_create_actions: ActionRef[];
+}
+
+export type ActionKind =
+  | { kind: "terminal"; terminal: string; new_window: boolean }
+  /** Without an app, the repository's editor is used */
+  | { kind: "editor"; app?: string | null }
+  | { kind: "script"; path: string; args: string[] }
+  | { kind: "url"; url: string }
+  | { kind: "command"; command: string };
+
+export type ActionDef = { id: string; label: string } & ActionKind;
+
+export interface ActionRef {
+  id: string;
 }
 
 export interface ScriptResult {
@@ -127,4 +487,35 @@ export interface ScriptResult {
   stdout: string;
   stderr: string;
   exit_code: number | null;
+  command: string;
+  cwd: string;
+  duration_ms: number;
+}
+
+export interface ActionOutcome {
+  action_id: string;
+  /** Output of script actions */
+  result: ScriptResult | null;
+  error: string | null;
+}
+
+/** Payload of the on-create-actions-finished event */
+export interface OnCreateActionsFinished {
+  worktree_path: string;
+  outcomes: ActionOutcome[];
+}
+
+export interface GitDirs {
+  git_dir: string;
+  common_dir: string;
+}
+
+export interface GcResult {
+  size_before: number;
+  size_after: number;
+}
+
+export interface GitProgress {
+  repo_path: string;
+  message: string;
 }
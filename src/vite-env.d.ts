@@ -1,2 +1,7 @@
 /// <reference types="svelte" />
 /// <reference types="vite/client" />
+
+interface Window {
+  /** Repository to open, set by open_repo_window */
+  __WOODEYE_REPO__?: string;
+}